@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+
+use crate::parser_core::ast::{AST, AST_type, Expression};
+use crate::parser_core::value::Value;
+use crate::executer::builtins::Builtin;
+use crate::diagnostics::diagnostic::Diagnostic;
+
+// Variables bound by the interpreter before the program runs (see Interpreter::bind_args) and the builtin functions
+fn is_predefined(name: &str) -> bool {
+    name == "argc"
+        || name.strip_prefix("arg").is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        || Builtin::from_name(name).is_some()
+}
+
+// Static checks run between parsing and execution, they only produce warnings and never stop a program from running
+pub struct Analyzer<'a> {
+    ast: &'a AST,
+}
+
+impl<'a> Analyzer<'a> {
+    pub fn new(ast: &'a AST) -> Self {
+        Analyzer {
+            ast,
+        }
+    }
+
+    // Collect every variable read by an expression alongside the node that reads it
+    fn collect_reads<'e>(expression: &'e Expression, reads: &mut Vec<(&'e str, &'e Expression)>) {
+        match expression {
+            Expression::Noun(Value::VarName(name), _) => reads.push((name, expression)),
+            Expression::Noun(_, _) => {},
+            Expression::Unary(_, operand, _) | Expression::Cast(operand, _, _) => Self::collect_reads(operand, reads),
+            Expression::Binary(_, left, right, _) => {
+                Self::collect_reads(left, reads);
+                Self::collect_reads(right, reads);
+            },
+            Expression::Call(name, args, _) => {
+                reads.push((name, expression));
+                for arg in args {
+                    Self::collect_reads(arg, reads);
+                }
+            },
+            Expression::List(items, _) | Expression::Concat(items, _) => {
+                for item in items {
+                    Self::collect_reads(item, reads);
+                }
+            },
+            Expression::Index(list, index, _) => {
+                Self::collect_reads(list, reads);
+                Self::collect_reads(index, reads);
+            },
+        }
+    }
+
+    // Names a block assigns, functions count as assignments of their name
+    fn collect_assigned(ast: &'a AST, assigned: &mut HashSet<&'a str>) {
+        for statement in &ast.statements {
+            if let (AST_type::Set | AST_type::Function, Value::VarName(name)) = (&statement.statement_type, &statement.a) {
+                assigned.insert(name);
+            }
+        }
+    }
+
+    // A function body sees the globals plus its own parameters and locals, never the variables of its caller
+    fn check_block(ast: &'a AST, visible: &HashSet<&'a str>, globals: &HashSet<&'a str>, reported: &mut HashSet<&'a str>, diagnostics: &mut Vec<Diagnostic>) {
+        for statement in &ast.statements {
+            if let Expression::Noun(Value::Function(function), _) = &statement.b {
+                let mut scope = globals.clone();
+                scope.extend(function.params.iter().map(|param| param.name.as_str()));
+                Self::collect_assigned(&function.body, &mut scope);
+                Self::check_block(&function.body, &scope, globals, reported, diagnostics);
+                continue;
+            }
+
+            let mut reads = Vec::new();
+            Self::collect_reads(&statement.b, &mut reads);
+
+            for (name, expression) in reads {
+                if !visible.contains(name) && !is_predefined(name) && reported.insert(name) {
+                    diagnostics.push(
+                        Diagnostic::warning("W0301", format!("variable `{}` is never assigned", name), Some(expression.span().clone()))
+                            .with_note("reading it will always produce undefined".to_string()),
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn run(&self) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+        // The check is flow insensitive: reading a variable is fine as long as some statement in scope assigns it
+        let mut globals: HashSet<&str> = HashSet::new();
+        Self::collect_assigned(self.ast, &mut globals);
+
+        let mut reported: HashSet<&str> = HashSet::new();
+        Self::check_block(self.ast, &globals, &globals, &mut reported, &mut diagnostics);
+
+        diagnostics
+    }
+}
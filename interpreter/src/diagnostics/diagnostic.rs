@@ -0,0 +1,84 @@
+use std::fmt;
+
+use crate::parser_core::span::Span;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NoteKind {
+    Note,   // Extra context about the problem
+    Help,   // A suggestion on how to fix the problem
+}
+
+#[derive(Clone, Debug)]
+pub struct Note {
+    pub kind: NoteKind,
+    pub message: String,
+}
+
+// A single problem found while reading, parsing, analyzing or running a file
+// **NOTE:** Codes are grouped by stage, E00xx file access, E01xx lexer, E02xx parser, W03xx analyzer, E04xx interpreter
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Option<Span>,    // None when the problem is not tied to a location (e.g. an unreadable file)
+    pub notes: Vec<Note>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: String, span: Option<Span>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message,
+            span,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn warning(code: &'static str, message: String, span: Option<Span>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            code,
+            message,
+            span,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_note(mut self, message: String) -> Self {
+        self.notes.push(Note { kind: NoteKind::Note, message });
+        self
+    }
+
+    pub fn with_help(mut self, message: String) -> Self {
+        self.notes.push(Note { kind: NoteKind::Help, message });
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity.name(), self.code, self.message)?;
+        if let Some(span) = &self.span {
+            write!(f, "\n  --> {}", span)?;
+        }
+
+        Ok(())
+    }
+}
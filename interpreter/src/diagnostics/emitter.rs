@@ -0,0 +1,47 @@
+use std::rc::Rc;
+
+use crate::diagnostics::diagnostic::Diagnostic;
+use crate::diagnostics::json;
+use crate::diagnostics::render::Renderer;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorFormat {
+    Human,   // rustc style snippets (see render.rs)
+    Json,    // One JSON object per line (see json.rs)
+}
+
+impl ErrorFormat {
+    pub fn from_name(name: &str) -> Option<ErrorFormat> {
+        match name {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+// Writes diagnostics to stderr in the selected format, stdout is left to the program being run
+pub struct Emitter {
+    format: ErrorFormat,
+    renderer: Renderer,
+}
+
+impl Emitter {
+    pub fn new(format: ErrorFormat, renderer: Renderer) -> Self {
+        Emitter {
+            format,
+            renderer,
+        }
+    }
+
+    pub fn add_source(&mut self, file: Rc<str>, contents: String) {
+        self.renderer.add_source(file, contents);
+    }
+
+    pub fn emit(&self, diagnostic: &Diagnostic) {
+        match self.format {
+            ErrorFormat::Human => eprintln!("{}", self.renderer.render(diagnostic)),
+            ErrorFormat::Json => eprintln!("{}", json::to_json(diagnostic).encode()),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::diagnostics::diagnostic::{Diagnostic, NoteKind};
+use crate::json::Json;
+use crate::parser_core::span::Span;
+
+// Version of the diagnostic schema, bumped whenever a field is removed or changes meaning (adding fields is not a breaking change)
+pub const SCHEMA_VERSION: i64 = 1;
+
+pub fn span_to_json(span: &Span) -> Json {
+    Json::object(vec![
+        ("line", Json::Int(span.line as i64)),
+        ("column", Json::Int(span.column as i64)),
+        ("end_line", Json::Int(span.end_line as i64)),
+        ("end_column", Json::Int(span.end_column as i64)),
+    ])
+}
+
+// Converts a diagnostic to the documented JSON schema (see DOCUMENTATION.md), emitted as one object per line
+pub fn to_json(diagnostic: &Diagnostic) -> Json {
+    let (file, span) = match &diagnostic.span {
+        Some(span) => (Json::Str(span.file.to_string()), span_to_json(span)),
+        None => (Json::Null, Json::Null),
+    };
+
+    let notes = diagnostic.notes.iter()
+        .map(|note| Json::object(vec![
+            ("kind", Json::Str(match note.kind {
+                NoteKind::Note => "note",
+                NoteKind::Help => "help",
+            }.to_string())),
+            ("message", Json::Str(note.message.clone())),
+        ]))
+        .collect();
+
+    Json::object(vec![
+        ("version", Json::Int(SCHEMA_VERSION)),
+        ("code", Json::Str(diagnostic.code.to_string())),
+        ("severity", Json::Str(diagnostic.severity.name().to_string())),
+        ("message", Json::Str(diagnostic.message.clone())),
+        ("file", file),
+        ("span", span),
+        ("notes", Json::Array(notes)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn emits_every_schema_field() {
+        let span = Span::new(Rc::from("main.luma"), 2, 5, 2, 9);
+        let diagnostic = Diagnostic::error("E0203", "unclosed '('".to_string(), Some(span))
+            .with_help("add a ')' to close the group".to_string());
+
+        assert_eq!(to_json(&diagnostic).encode(), concat!(
+            r#"{"version":1,"code":"E0203","severity":"error","message":"unclosed '('","file":"main.luma","#,
+            r#""span":{"line":2,"column":5,"end_line":2,"end_column":9},"#,
+            r#""notes":[{"kind":"help","message":"add a ')' to close the group"}]}"#,
+        ));
+    }
+
+    #[test]
+    fn missing_span_is_null() {
+        let diagnostic = Diagnostic::error("E0001", "could not read `a.luma`".to_string(), None);
+        let encoded = to_json(&diagnostic).encode();
+        assert!(encoded.contains(r#""file":null,"span":null"#));
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::diagnostics::diagnostic::{Diagnostic, NoteKind, Severity};
+
+// ANSI escape codes used by the colored mode
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenderMode {
+    Plain,
+    Color,
+}
+
+// Renders diagnostics in the style of rustc:
+//
+// error[E0203]: unclosed '('
+//  --> main.luma:4:1
+//   |
+// 4 | (y * 2
+//   | ^
+//   = help: add a ')' to close the group
+pub struct Renderer {
+    mode: RenderMode,
+    sources: HashMap<Rc<str>, String>,   // File name to file contents, used to excerpt the offending line
+}
+
+impl Renderer {
+    pub fn new(mode: RenderMode) -> Self {
+        Renderer {
+            mode,
+            sources: HashMap::new(),
+        }
+    }
+
+    pub fn add_source(&mut self, file: Rc<str>, contents: String) {
+        self.sources.insert(file, contents);
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        match self.mode {
+            RenderMode::Plain => text.to_string(),
+            RenderMode::Color => format!("{}{}{}", color, text, RESET),
+        }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let color = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        let mut output = format!(
+            "{}{}\n",
+            self.paint(color, &format!("{}[{}]", diagnostic.severity.name(), diagnostic.code)),
+            self.paint(BOLD, &format!(": {}", diagnostic.message)),
+        );
+
+        // Width of the line number gutter, every gutter line is padded to it so the bars line up
+        let mut gutter = 1;
+
+        if let Some(span) = &diagnostic.span {
+            gutter = span.line.to_string().len();
+            let padding = " ".repeat(gutter);
+
+            output += &format!("{}{} {}\n", padding, self.paint(BLUE, "-->"), span);
+
+            let line = self.sources.get(&span.file).and_then(|contents| contents.lines().nth(span.line - 1));
+            if let Some(line) = line {
+                let line = line.trim_end();
+                let line_length = line.chars().count();
+
+                // Spans that continue onto later lines are underlined up to the end of their first line
+                let end_column = if span.end_line == span.line { span.end_column } else { line_length };
+                let underline_length = end_column.saturating_sub(span.column) + 1;
+
+                output += &format!("{} {}\n", padding, self.paint(BLUE, "|"));
+                output += &format!("{} {} {}\n", self.paint(BLUE, &span.line.to_string()), self.paint(BLUE, "|"), line);
+                output += &format!(
+                    "{} {} {}{}\n",
+                    padding,
+                    self.paint(BLUE, "|"),
+                    " ".repeat(span.column - 1),
+                    self.paint(color, &"^".repeat(underline_length)),
+                );
+            }
+        }
+
+        for note in &diagnostic.notes {
+            let label = match note.kind {
+                NoteKind::Note => "note",
+                NoteKind::Help => "help",
+            };
+
+            output += &format!("{} {} {}: {}\n", " ".repeat(gutter), self.paint(BLUE, "="), self.paint(CYAN, label), note.message);
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser_core::span::Span;
+
+    fn renderer(mode: RenderMode) -> Renderer {
+        let mut renderer = Renderer::new(mode);
+        renderer.add_source(Rc::from("main.luma"), "x = 1;\ny = (x + 2;\n".to_string());
+        renderer
+    }
+
+    #[test]
+    fn renders_plain_snippet_with_underline() {
+        let span = Span::new(Rc::from("main.luma"), 2, 5, 2, 9);
+        let diagnostic = Diagnostic::error("E0203", "unclosed '('".to_string(), Some(span))
+            .with_help("add a ')' to close the group".to_string());
+
+        assert_eq!(renderer(RenderMode::Plain).render(&diagnostic), concat!(
+            "error[E0203]: unclosed '('\n",
+            " --> main.luma:2:5\n",
+            "  |\n",
+            "2 | y = (x + 2;\n",
+            "  |     ^^^^^\n",
+            "  = help: add a ')' to close the group\n",
+        ));
+    }
+
+    #[test]
+    fn renders_without_snippet_when_span_is_missing() {
+        let diagnostic = Diagnostic::error("E0001", "could not read `a.luma`".to_string(), None);
+        assert_eq!(renderer(RenderMode::Plain).render(&diagnostic), "error[E0001]: could not read `a.luma`\n");
+    }
+
+    #[test]
+    fn colored_mode_wraps_labels_in_escape_codes() {
+        let span = Span::new(Rc::from("main.luma"), 1, 1, 1, 1);
+        let diagnostic = Diagnostic::warning("W0301", "unused".to_string(), Some(span));
+        let rendered = renderer(RenderMode::Color).render(&diagnostic);
+
+        assert!(rendered.starts_with(&format!("{}warning[W0301]{}", YELLOW, RESET)));
+        assert!(rendered.contains(&format!("{}^{}", YELLOW, RESET)));
+    }
+}
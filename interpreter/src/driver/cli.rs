@@ -0,0 +1,195 @@
+use crate::diagnostics::emitter::ErrorFormat;
+use crate::driver::emit::{EmitFormat, EmitOptions, Stage};
+
+pub const USAGE: &str = "\
+usage: luma [options] <command>
+
+commands:
+    run <file> [args...]    run a script, trailing arguments are passed to the script
+    check <file>            report syntax errors and warnings without running the script
+    repl                    start an interactive session
+
+options:
+    --error-format <human|json>    how diagnostics are written to stderr (default: human)
+    --emit <tokens|ast|ir>[,...]   print intermediate forms to stdout instead of running the script
+    --emit-format <pretty|json>    how --emit output is written (default: pretty)
+    --strict                       report operations that would produce undefined as errors
+    -h, --help                     print this message";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run { file: String, args: Vec<String> },
+    Check { file: String },
+    Repl,
+    Help,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub command: Command,
+    pub error_format: ErrorFormat,
+    pub emit: EmitOptions,
+    pub strict: bool,   // Strict mode for every file run, as if each started with `#strict`
+}
+
+// Value of an option given either as `--name value` or `--name=value`
+fn option_value<I: Iterator<Item = String>>(arg: &str, name: &str, args: &mut I) -> Result<Option<String>, String> {
+    if arg == name {
+        return Ok(args.next());
+    }
+
+    match arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+        Some(val) => Ok(Some(val.to_string())),
+        None => Err(format!("unknown option `{}`", arg)),
+    }
+}
+
+// Options may appear anywhere before the script path, everything after the script path (or after `--`) belongs to the script
+pub fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut error_format = ErrorFormat::Human;
+    let mut emit = EmitOptions::none();
+    let mut strict = false;
+    let mut positional: Vec<String> = Vec::new();
+    let mut script_args: Vec<String> = Vec::new();
+
+    while let Some(arg) = args.next() {
+        // `run <file>` stops option parsing so flags can be forwarded to the script untouched
+        if positional.len() == 2 && positional[0] == "run" {
+            script_args.push(arg);
+            script_args.extend(args.by_ref());
+            break;
+        }
+
+        if arg == "--" {
+            script_args.extend(args.by_ref());
+            break;
+        }
+
+        if arg == "-h" || arg == "--help" {
+            return Ok(Options { command: Command::Help, error_format, emit, strict });
+        }
+
+        if arg == "--strict" {
+            strict = true;
+            continue;
+        }
+
+        if arg.starts_with("--error-format") {
+            error_format = match option_value(&arg, "--error-format", &mut args)?.as_deref().and_then(ErrorFormat::from_name) {
+                Some(val) => val,
+                None => return Err("expected `human` or `json` after --error-format".to_string()),
+            };
+            continue;
+        }
+
+        if arg.starts_with("--emit-format") {
+            emit.format = match option_value(&arg, "--emit-format", &mut args)?.as_deref().and_then(EmitFormat::from_name) {
+                Some(val) => val,
+                None => return Err("expected `pretty` or `json` after --emit-format".to_string()),
+            };
+            continue;
+        }
+
+        if arg.starts_with("--emit") {
+            let stages = option_value(&arg, "--emit", &mut args)?.unwrap_or_default();
+            for name in stages.split(',') {
+                match Stage::from_name(name) {
+                    Some(stage) => emit.stages.push(stage),
+                    None => return Err(format!("unknown --emit stage `{}`, expected tokens, ast or ir", name)),
+                }
+            }
+            continue;
+        }
+
+        if arg.starts_with('-') && arg.len() > 1 {
+            return Err(format!("unknown option `{}`", arg));
+        }
+
+        positional.push(arg);
+    }
+
+    if !script_args.is_empty() && positional.first().map(String::as_str) != Some("run") {
+        return Err("only `run` accepts script arguments".to_string());
+    }
+
+    let mut positional = positional.into_iter();
+    let command = match positional.next().as_deref() {
+        Some("run") => match positional.next() {
+            Some(file) => Command::Run { file, args: script_args },
+            None => return Err("`run` expects a file".to_string()),
+        },
+        Some("check") => match positional.next() {
+            Some(file) => Command::Check { file },
+            None => return Err("`check` expects a file".to_string()),
+        },
+        Some("repl") => Command::Repl,
+        Some(other) => return Err(format!("unknown command `{}`", other)),
+        None => Command::Help,
+    };
+
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument `{}`", extra));
+    }
+
+    if !emit.is_empty() && !matches!(command, Command::Run { .. } | Command::Check { .. }) {
+        return Err("--emit needs a file to `run` or `check`".to_string());
+    }
+
+    if strict && !matches!(command, Command::Run { .. } | Command::Repl) {
+        return Err("--strict only applies to `run` and `repl`".to_string());
+    }
+
+    Ok(Options { command, error_format, emit, strict })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn forwards_everything_after_the_script_path() {
+        let options = parse(&["--error-format", "json", "run", "main.luma", "5", "--verbose"]).unwrap();
+        assert_eq!(options.error_format, ErrorFormat::Json);
+        assert_eq!(options.command, Command::Run {
+            file: "main.luma".to_string(),
+            args: vec!["5".to_string(), "--verbose".to_string()],
+        });
+    }
+
+    #[test]
+    fn parses_check_and_repl() {
+        assert_eq!(parse(&["check", "main.luma"]).unwrap().command, Command::Check { file: "main.luma".to_string() });
+        assert_eq!(parse(&["repl", "--error-format=json"]).unwrap().command, Command::Repl);
+        assert_eq!(parse(&[]).unwrap().command, Command::Help);
+    }
+
+    #[test]
+    fn parses_strict_flag() {
+        assert!(parse(&["--strict", "run", "main.luma"]).unwrap().strict);
+        assert!(!parse(&["run", "main.luma", "--strict"]).unwrap().strict);
+        assert!(parse(&["--strict", "check", "main.luma"]).is_err());
+    }
+
+    #[test]
+    fn parses_emit_stages() {
+        let options = parse(&["--emit", "tokens,ir", "--emit-format=json", "check", "main.luma"]).unwrap();
+        assert_eq!(options.emit.stages, [Stage::Tokens, Stage::Ir]);
+        assert_eq!(options.emit.format, EmitFormat::Json);
+
+        assert!(parse(&["--emit=bytecode", "check", "main.luma"]).is_err());
+        assert!(parse(&["--emit", "ast", "repl"]).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_invocations() {
+        assert!(parse(&["run"]).is_err());
+        assert!(parse(&["check", "a.luma", "b.luma"]).is_err());
+        assert!(parse(&["build", "a.luma"]).is_err());
+        assert!(parse(&["--error-format", "xml", "repl"]).is_err());
+        assert!(parse(&["repl", "--", "x"]).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::analyzer::analyzer::Analyzer;
+use crate::diagnostics::diagnostic::{Diagnostic, Severity};
+use crate::diagnostics::emitter::Emitter;
+use crate::driver::emit::EmitOptions;
+use crate::executer::interpreter::Interpreter;
+use crate::parser_core::ast::AST;
+use crate::parser_core::lexer::Lexer;
+use crate::parser_core::parser::Parser;
+use crate::parser_core::value::{CastTo, Value};
+
+// Process exit codes
+pub const SUCCESS: i32 = 0;
+pub const FAILURE: i32 = 1;   // The script failed to parse or run
+pub const USAGE: i32 = 2;     // The command line itself was invalid
+
+// Lex and parse a source, collecting the diagnostics of both stages so every problem is reported in one run
+pub fn parse(mut lexer: Lexer, emit: &EmitOptions) -> Result<AST, Vec<Diagnostic>> {
+    let lexed = lexer.run();
+
+    let parser = Parser::new(lexer);
+    emit.tokens(parser.tokens());
+
+    match (lexed, parser.run()) {
+        (Ok(()), Ok(ast)) => {
+            emit.ast(&ast);
+            Ok(ast)
+        },
+        (Err(mut lex_errors), Err(parse_errors)) => {
+            lex_errors.extend(parse_errors);
+            Err(lex_errors)
+        },
+        (Err(errors), _) | (_, Err(errors)) => Err(errors),
+    }
+}
+
+// Emits every diagnostic and returns whether any of them was an error
+pub fn emit_all(emitter: &Emitter, diagnostics: &[Diagnostic]) -> bool {
+    for diagnostic in diagnostics {
+        emitter.emit(diagnostic);
+    }
+
+    diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+// Parse and analyze a file, every diagnostic is emitted and None is returned if any of them is an error
+fn load(file: &str, emitter: &mut Emitter, emit: &EmitOptions) -> Option<AST> {
+    let lexer = match Lexer::new(file.to_string()) {
+        Ok(val) => val,
+        Err(err) => {
+            emitter.emit(&err);
+            return None;
+        },
+    };
+    emitter.add_source(lexer.file_name.clone(), lexer.file_contents.clone());
+
+    let ast = match parse(lexer, emit) {
+        Ok(val) => val,
+        Err(errors) => {
+            emit_all(emitter, &errors);
+            return None;
+        },
+    };
+
+    if emit_all(emitter, &Analyzer::new(&ast).run()) {
+        return None;
+    }
+
+    Some(ast)
+}
+
+pub fn display(val: &Value) -> String {
+    match CastTo::<String>::cast_to(val) {
+        Some(Value::Str(s)) => s,
+        _ => "undefined".to_string(),
+    }
+}
+
+pub fn run(file: &str, args: &[String], strict: bool, emitter: &mut Emitter, emit: &EmitOptions) -> i32 {
+    let ast = match load(file, emitter, emit) {
+        Some(val) => val,
+        None => return FAILURE,
+    };
+
+    // Dumping intermediate forms replaces running the script
+    if !emit.is_empty() {
+        return SUCCESS;
+    }
+
+    let mut interpreter = Interpreter::new(ast);
+    interpreter.bind_args(args);
+    if strict {
+        interpreter.enable_strict();
+    }
+
+    // The top level runs first so `main` can use every global, without a `main` the script's own result is the program's result
+    let result = interpreter.run().and_then(|val| Ok(interpreter.run_main(args)?.map_or((val, false), |val| (val, true))));
+
+    match result {
+        // The `int` returned by `main` becomes the exit code
+        Ok((Value::Int(code), true)) => code,
+        Ok((Value::Undefined, _)) => SUCCESS,
+        Ok((val, _)) => {
+            println!("{}", display(&val));
+            SUCCESS
+        },
+        Err(err) => {
+            emitter.emit(&err);
+            FAILURE
+        },
+    }
+}
+
+pub fn check(file: &str, emitter: &mut Emitter, emit: &EmitOptions) -> i32 {
+    match load(file, emitter, emit) {
+        Some(_) => SUCCESS,
+        None => FAILURE,
+    }
+}
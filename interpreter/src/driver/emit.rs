@@ -0,0 +1,615 @@
+use crate::diagnostics::json::span_to_json;
+use crate::json::Json;
+use crate::parser_core::ast::{AST, AST_statement, AST_type, Expression, Function, Lookup};
+use crate::parser_core::span::Span;
+use crate::parser_core::tokenized::{Segment, Token, Tokenized};
+use crate::parser_core::value::Value;
+
+// Intermediate forms that can be dumped with --emit, in pipeline order
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stage {
+    Tokens,
+    Ast,
+    Ir,
+}
+
+impl Stage {
+    pub fn from_name(name: &str) -> Option<Stage> {
+        match name {
+            "tokens" => Some(Stage::Tokens),
+            "ast" => Some(Stage::Ast),
+            "ir" => Some(Stage::Ir),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmitFormat {
+    Pretty,
+    Json,
+}
+
+impl EmitFormat {
+    pub fn from_name(name: &str) -> Option<EmitFormat> {
+        match name {
+            "pretty" => Some(EmitFormat::Pretty),
+            "json" => Some(EmitFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+// Which stages to print to stdout and how, when any stage is requested the script is not run
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmitOptions {
+    pub stages: Vec<Stage>,
+    pub format: EmitFormat,
+}
+
+impl EmitOptions {
+    pub fn none() -> Self {
+        EmitOptions {
+            stages: Vec::new(),
+            format: EmitFormat::Pretty,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    pub fn tokens(&self, tokenized: &Tokenized) {
+        if self.stages.contains(&Stage::Tokens) {
+            println!("{}", match self.format {
+                EmitFormat::Pretty => tokens_pretty(tokenized),
+                EmitFormat::Json => tokens_json(tokenized).encode(),
+            });
+        }
+    }
+
+    pub fn ast(&self, ast: &AST) {
+        if self.stages.contains(&Stage::Ast) {
+            println!("{}", match self.format {
+                EmitFormat::Pretty => ast_pretty(ast),
+                EmitFormat::Json => ast_json(ast).encode(),
+            });
+        }
+
+        if self.stages.contains(&Stage::Ir) {
+            println!("{}", match self.format {
+                EmitFormat::Pretty => ir_pretty(ast),
+                EmitFormat::Json => ir_json(ast).encode(),
+            });
+        }
+    }
+}
+
+fn span_text(span: &Span) -> String {
+    format!("{}:{}-{}:{}", span.line, span.column, span.end_line, span.end_column)
+}
+
+// Source like spelling of a value, strings and chars are quoted so they can't be confused with variable names
+fn value_text(val: &Value) -> String {
+    match val {
+        Value::Int(n) => n.to_string(),
+        Value::Long(n) => format!("{}L", n),
+        Value::BigInt(n) => format!("{}n", n),
+        Value::Float(f) => format!("{:?}", f),
+        Value::Str(s) => format!("{:?}", s),
+        Value::Char(c) => format!("{:?}", c),
+        Value::Bool(b) => b.to_string(),
+        Value::VarName(name) => name.clone(),
+        Value::Function(function) => format!("<function {}>", function.name),
+        Value::List(items) => format!("[{}]", items.iter().map(value_text).collect::<Vec<String>>().join(", ")),
+        Value::Undefined => "undefined".to_string(),
+    }
+}
+
+// The inside of a string literal, braces are escaped so they aren't read back as interpolations
+fn escaped(text: &str) -> String {
+    text.chars()
+        .map(|character| match character {
+            '{' | '}' => format!("\\{}", character),
+            other => other.escape_debug().to_string(),
+        })
+        .collect()
+}
+
+// Source like spelling of a token, `"a {b + 1}"` for an interpolated string
+fn token_text(token: &Token) -> String {
+    match token {
+        Token::Verb(verb, _) => verb.symbol().to_string(),
+        Token::Noun(val, _) => value_text(val),
+        Token::Keyword(keyword, _) => keyword.name().to_string(),
+        Token::Interpolated(segments, _) => {
+            let segments: Vec<String> = segments.iter()
+                .map(|segment| match segment {
+                    Segment::Text(text, _) => escaped(text),
+                    Segment::Expression(tokens, _) => format!("{{{}}}", tokens.iter().map(token_text).collect::<Vec<String>>().join(" ")),
+                })
+                .collect();
+            format!("\"{}\"", segments.concat())
+        },
+    }
+}
+
+fn value_json(val: &Value) -> Json {
+    let inner = match val {
+        Value::Int(n) => Json::Int(*n as i64),
+        Value::Long(n) => Json::Int(*n),
+        Value::BigInt(n) => Json::Str(n.to_string()),   // Digits as text, JSON readers tend to lose precision past 53 bits
+        Value::Float(f) => Json::Float(*f),
+        Value::Str(s) => Json::Str(s.clone()),
+        Value::Char(c) => Json::Str(c.to_string()),
+        Value::Bool(b) => Json::Bool(*b),
+        Value::VarName(name) => Json::Str(name.clone()),
+        Value::Function(function) => Json::Str(function.name.clone()),
+        Value::List(items) => Json::Array(items.iter().map(value_json).collect()),
+        Value::Undefined => Json::Null,
+    };
+
+    Json::object(vec![
+        ("type", Json::Str(val.type_name())),
+        ("value", inner),
+    ])
+}
+
+// Fully parenthesized infix form, so precedence decisions are visible
+pub fn expression_text(expression: &Expression) -> String {
+    match expression {
+        Expression::Noun(val, _) => value_text(val),
+        Expression::Unary(verb, operand, _) => format!("({}{})", verb.symbol(), expression_text(operand)),
+        Expression::Binary(verb, left, right, _) => format!("({} {} {})", expression_text(left), verb.symbol(), expression_text(right)),
+        Expression::Call(name, args, _) => format!("{}({})", name, args.iter().map(expression_text).collect::<Vec<String>>().join(", ")),
+        Expression::List(items, _) => format!("[{}]", items.iter().map(expression_text).collect::<Vec<String>>().join(", ")),
+        Expression::Index(list, index, _) => format!("{}[{}]", expression_text(list), expression_text(index)),
+        Expression::Cast(operand, target, _) => format!("({}:{})", expression_text(operand), target.name()),
+        Expression::Concat(parts, _) => {
+            let parts: Vec<String> = parts.iter()
+                .map(|part| match part {
+                    Expression::Noun(Value::Str(text), _) => escaped(text),
+                    other => format!("{{{}}}", expression_text(other)),
+                })
+                .collect();
+            format!("\"{}\"", parts.concat())
+        },
+    }
+}
+
+// `name(a: int, b): int`, the declaration without its body
+fn signature_text(function: &Function) -> String {
+    let params: Vec<String> = function.params.iter()
+        .map(|param| match &param.param_type {
+            Some(param_type) => format!("{}: {}", param.name, param_type.name()),
+            None => param.name.clone(),
+        })
+        .collect();
+
+    match &function.return_type {
+        Some(return_type) => format!("{}({}): {}", function.name, params.join(", "), return_type.name()),
+        None => format!("{}({})", function.name, params.join(", ")),
+    }
+}
+
+fn statement_function(statement: &AST_statement) -> Option<&Function> {
+    match &statement.b {
+        Expression::Noun(Value::Function(function), _) => Some(function),
+        _ => None,
+    }
+}
+
+// Every function declared in the statements, nested declarations included, in source order
+fn collect_functions<'a>(statements: &'a [AST_statement], functions: &mut Vec<&'a Function>) {
+    for function in statements.iter().filter_map(statement_function) {
+        functions.push(function);
+        collect_functions(&function.body.statements, functions);
+    }
+}
+
+fn tokens_pretty(tokenized: &Tokenized) -> String {
+    let mut output = String::new();
+
+    for token_list in &tokenized.lines {
+        let suffix = token_list.suffix.as_ref().map_or("none", |suffix| suffix.name());
+        output += &format!("statement {} suffix={}\n", span_text(&token_list.span), suffix);
+
+        for token in &token_list.objects {
+            let (kind, text) = match token {
+                Token::Verb(verb, _) => ("verb", verb.symbol().to_string()),
+                Token::Noun(val, _) => ("noun", format!("{} {}", val.type_name(), value_text(val))),
+                Token::Keyword(keyword, _) => ("keyword", keyword.name().to_string()),
+                Token::Interpolated(_, _) => ("interpolated", token_text(token)),
+            };
+            output += &format!("    {:<12} {} {}\n", span_text(token.span()), kind, text);
+        }
+    }
+
+    output.trim_end().to_string()
+}
+
+fn token_json(token: &Token) -> Json {
+    match token {
+        Token::Verb(verb, span) => Json::object(vec![
+            ("kind", Json::Str("verb".to_string())),
+            ("verb", Json::Str(verb.symbol().to_string())),
+            ("span", span_to_json(span)),
+        ]),
+        Token::Noun(val, span) => Json::object(vec![
+            ("kind", Json::Str("noun".to_string())),
+            ("value", value_json(val)),
+            ("span", span_to_json(span)),
+        ]),
+        Token::Keyword(keyword, span) => Json::object(vec![
+            ("kind", Json::Str("keyword".to_string())),
+            ("keyword", Json::Str(keyword.name().to_string())),
+            ("span", span_to_json(span)),
+        ]),
+        Token::Interpolated(segments, span) => {
+            let segments = segments.iter()
+                .map(|segment| match segment {
+                    Segment::Text(text, span) => Json::object(vec![
+                        ("kind", Json::Str("text".to_string())),
+                        ("text", Json::Str(text.clone())),
+                        ("span", span_to_json(span)),
+                    ]),
+                    Segment::Expression(tokens, span) => Json::object(vec![
+                        ("kind", Json::Str("expression".to_string())),
+                        ("tokens", Json::Array(tokens.iter().map(token_json).collect())),
+                        ("span", span_to_json(span)),
+                    ]),
+                })
+                .collect();
+
+            Json::object(vec![
+                ("kind", Json::Str("interpolated".to_string())),
+                ("segments", Json::Array(segments)),
+                ("span", span_to_json(span)),
+            ])
+        },
+    }
+}
+
+fn tokens_json(tokenized: &Tokenized) -> Json {
+    let statements = tokenized.lines.iter()
+        .map(|token_list| {
+            Json::object(vec![
+                ("suffix", token_list.suffix.as_ref().map_or(Json::Null, |suffix| Json::Str(suffix.name().to_string()))),
+                ("span", span_to_json(&token_list.span)),
+                ("tokens", Json::Array(token_list.objects.iter().map(token_json).collect())),
+            ])
+        })
+        .collect();
+
+    Json::object(vec![("statements", Json::Array(statements))])
+}
+
+fn statement_name(statement: &AST_statement) -> &'static str {
+    match statement.statement_type {
+        AST_type::Set => "set",
+        AST_type::Return => "return",
+        AST_type::Function => "function",
+        AST_type::Mark => "mark",
+        AST_type::Do(_) => "do",
+        AST_type::Unset => "unset",
+    }
+}
+
+fn lookup_name(lookup: &Lookup) -> &'static str {
+    match lookup {
+        Lookup::Forward => "forward",
+        Lookup::Back => "back",
+        Lookup::Anywhere => "anywhere",
+    }
+}
+
+fn expression_pretty(expression: &Expression, depth: usize, output: &mut String) {
+    let indent = "    ".repeat(depth);
+
+    match expression {
+        Expression::Noun(val, span) => {
+            *output += &format!("{}Noun {} {} @{}\n", indent, val.type_name(), value_text(val), span_text(span));
+        },
+        Expression::Unary(verb, operand, span) => {
+            *output += &format!("{}Unary {} @{}\n", indent, verb.symbol(), span_text(span));
+            expression_pretty(operand, depth + 1, output);
+        },
+        Expression::Binary(verb, left, right, span) => {
+            *output += &format!("{}Binary {} @{}\n", indent, verb.symbol(), span_text(span));
+            expression_pretty(left, depth + 1, output);
+            expression_pretty(right, depth + 1, output);
+        },
+        Expression::Call(name, args, span) => {
+            *output += &format!("{}Call {} @{}\n", indent, name, span_text(span));
+            for arg in args {
+                expression_pretty(arg, depth + 1, output);
+            }
+        },
+        Expression::List(items, span) => {
+            *output += &format!("{}List @{}\n", indent, span_text(span));
+            for item in items {
+                expression_pretty(item, depth + 1, output);
+            }
+        },
+        Expression::Index(list, index, span) => {
+            *output += &format!("{}Index @{}\n", indent, span_text(span));
+            expression_pretty(list, depth + 1, output);
+            expression_pretty(index, depth + 1, output);
+        },
+        Expression::Cast(operand, target, span) => {
+            *output += &format!("{}Cast {} @{}\n", indent, target.name(), span_text(span));
+            expression_pretty(operand, depth + 1, output);
+        },
+        Expression::Concat(parts, span) => {
+            *output += &format!("{}Concat @{}\n", indent, span_text(span));
+            for part in parts {
+                expression_pretty(part, depth + 1, output);
+            }
+        },
+    }
+}
+
+fn expression_json(expression: &Expression) -> Json {
+    match expression {
+        Expression::Noun(val, span) => Json::object(vec![
+            ("kind", Json::Str("noun".to_string())),
+            ("value", value_json(val)),
+            ("span", span_to_json(span)),
+        ]),
+        Expression::Unary(verb, operand, span) => Json::object(vec![
+            ("kind", Json::Str("unary".to_string())),
+            ("verb", Json::Str(verb.symbol().to_string())),
+            ("operand", expression_json(operand)),
+            ("span", span_to_json(span)),
+        ]),
+        Expression::Binary(verb, left, right, span) => Json::object(vec![
+            ("kind", Json::Str("binary".to_string())),
+            ("verb", Json::Str(verb.symbol().to_string())),
+            ("left", expression_json(left)),
+            ("right", expression_json(right)),
+            ("span", span_to_json(span)),
+        ]),
+        Expression::Call(name, args, span) => Json::object(vec![
+            ("kind", Json::Str("call".to_string())),
+            ("function", Json::Str(name.clone())),
+            ("args", Json::Array(args.iter().map(expression_json).collect())),
+            ("span", span_to_json(span)),
+        ]),
+        Expression::List(items, span) => Json::object(vec![
+            ("kind", Json::Str("list".to_string())),
+            ("items", Json::Array(items.iter().map(expression_json).collect())),
+            ("span", span_to_json(span)),
+        ]),
+        Expression::Index(list, index, span) => Json::object(vec![
+            ("kind", Json::Str("index".to_string())),
+            ("list", expression_json(list)),
+            ("index", expression_json(index)),
+            ("span", span_to_json(span)),
+        ]),
+        Expression::Cast(operand, target, span) => Json::object(vec![
+            ("kind", Json::Str("cast".to_string())),
+            ("type", Json::Str(target.name().to_string())),
+            ("operand", expression_json(operand)),
+            ("span", span_to_json(span)),
+        ]),
+        Expression::Concat(parts, span) => Json::object(vec![
+            ("kind", Json::Str("concat".to_string())),
+            ("parts", Json::Array(parts.iter().map(expression_json).collect())),
+            ("span", span_to_json(span)),
+        ]),
+    }
+}
+
+fn statements_pretty(statements: &[AST_statement], depth: usize, output: &mut String) {
+    let indent = "    ".repeat(depth);
+
+    for statement in statements {
+        for line in statement.doc.iter().flat_map(|doc| doc.lines()) {
+            *output += &format!("{}/// {}\n", indent, line);
+        }
+
+        if let Some(function) = statement_function(statement) {
+            *output += &format!("{}function {} @{}\n", indent, signature_text(function), span_text(&statement.span));
+            statements_pretty(&function.body.statements, depth + 1, output);
+            continue;
+        }
+
+        let prefix = match &statement.statement_type {
+            AST_type::Do(lookup) => lookup.prefix(),
+            _ => "",
+        };
+        let target = match (&statement.a, &statement.declared_type) {
+            (Value::VarName(name), Some(declared_type)) => format!(" {}: {}", name, declared_type.name()),
+            (Value::VarName(name), None) => format!(" {}{}", prefix, name),
+            _ => String::new(),
+        };
+        *output += &format!("{}{}{} @{}\n", indent, statement_name(statement), target, span_text(&statement.span));
+
+        // Markers and unsets have no expression, the expression of a jump is its condition
+        if !matches!(statement.statement_type, AST_type::Mark | AST_type::Unset) {
+            expression_pretty(&statement.b, depth + 1, output);
+        }
+    }
+}
+
+fn ast_pretty(ast: &AST) -> String {
+    let mut output = String::new();
+    statements_pretty(&ast.statements, 0, &mut output);
+    output.trim_end().to_string()
+}
+
+fn statements_json(statements: &[AST_statement]) -> Json {
+    let statements = statements.iter()
+        .map(|statement| {
+            let target = match &statement.a {
+                Value::VarName(name) => Json::Str(name.clone()),
+                _ => Json::Null,
+            };
+
+            match (&statement.statement_type, statement_function(statement)) {
+                (AST_type::Mark | AST_type::Unset, _) => Json::object(vec![
+                    ("type", Json::Str(statement_name(statement).to_string())),
+                    ("target", target),
+                    ("span", span_to_json(&statement.span)),
+                ]),
+                (AST_type::Do(lookup), _) => Json::object(vec![
+                    ("type", Json::Str(statement_name(statement).to_string())),
+                    ("target", target),
+                    ("lookup", Json::Str(lookup_name(lookup).to_string())),
+                    ("condition", expression_json(&statement.b)),
+                    ("span", span_to_json(&statement.span)),
+                ]),
+                (_, Some(function)) => Json::object(vec![
+                    ("type", Json::Str(statement_name(statement).to_string())),
+                    ("target", target),
+                    ("params", Json::Array(function.params.iter()
+                        .map(|param| Json::object(vec![
+                            ("name", Json::Str(param.name.clone())),
+                            ("type", param.param_type.as_ref().map_or(Json::Null, |param_type| Json::Str(param_type.name().to_string()))),
+                        ]))
+                        .collect())),
+                    ("return_type", function.return_type.as_ref().map_or(Json::Null, |return_type| Json::Str(return_type.name().to_string()))),
+                    ("body", statements_json(&function.body.statements)),
+                    ("doc", statement.doc.clone().map_or(Json::Null, Json::Str)),
+                    ("span", span_to_json(&statement.span)),
+                ]),
+                (_, None) => Json::object(vec![
+                    ("type", Json::Str(statement_name(statement).to_string())),
+                    ("target", target),
+                    ("declared_type", statement.declared_type.as_ref().map_or(Json::Null, |declared_type| Json::Str(declared_type.name().to_string()))),
+                    ("expression", expression_json(&statement.b)),
+                    ("doc", statement.doc.clone().map_or(Json::Null, Json::Str)),
+                    ("span", span_to_json(&statement.span)),
+                ]),
+            }
+        })
+        .collect();
+
+    Json::Array(statements)
+}
+
+fn ast_json(ast: &AST) -> Json {
+    Json::object(vec![("statements", statements_json(&ast.statements))])
+}
+
+// The IR is the flat instruction list the interpreter steps through, operands are rendered as text like the prototype's ASTLine nouns
+// Jumps list the index of the marker they land on
+fn ir_operands(ast: &AST, index: usize) -> Vec<String> {
+    let statement = &ast.statements[index];
+    match &statement.statement_type {
+        AST_type::Set => match &statement.declared_type {
+            Some(declared_type) => vec![format!("{}: {}", value_text(&statement.a), declared_type.name()), expression_text(&statement.b)],
+            None => vec![value_text(&statement.a), expression_text(&statement.b)],
+        },
+        AST_type::Return => vec![expression_text(&statement.b)],
+        AST_type::Function => vec![statement_function(statement).map_or_else(String::new, signature_text)],
+        AST_type::Mark | AST_type::Unset => vec![value_text(&statement.a)],
+        AST_type::Do(lookup) => {
+            let name = value_text(&statement.a);
+            let target = ast.jump_target(&name, lookup, index).map_or_else(|| "?".to_string(), |target| format!("{:04}", target));
+            vec![format!("{}{} -> {}", lookup.prefix(), name, target), expression_text(&statement.b)]
+        },
+    }
+}
+
+fn ir_listing(ast: &AST) -> String {
+    ast.statements.iter()
+        .enumerate()
+        .map(|(index, statement)| format!("{:04}  {:<8} {}", index, statement_name(statement), ir_operands(ast, index).join(", ")))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Function bodies are listed after the program, each under a `name:` label and numbered from zero
+fn ir_pretty(ast: &AST) -> String {
+    let mut output = ir_listing(ast);
+
+    let mut functions = Vec::new();
+    collect_functions(&ast.statements, &mut functions);
+    for function in functions {
+        output += &format!("\n\n{}:\n{}", function.name, ir_listing(&function.body));
+    }
+
+    output
+}
+
+fn ir_instructions_json(ast: &AST) -> Json {
+    let instructions = ast.statements.iter()
+        .enumerate()
+        .map(|(index, statement)| Json::object(vec![
+            ("index", Json::Int(index as i64)),
+            ("op", Json::Str(statement_name(statement).to_string())),
+            ("operands", Json::Array(ir_operands(ast, index).into_iter().map(Json::Str).collect())),
+            ("line", Json::Int(statement.span.line as i64)),
+        ]))
+        .collect();
+
+    Json::Array(instructions)
+}
+
+fn ir_json(ast: &AST) -> Json {
+    let mut functions = Vec::new();
+    collect_functions(&ast.statements, &mut functions);
+
+    Json::object(vec![
+        ("instructions", ir_instructions_json(ast)),
+        ("functions", Json::Array(functions.into_iter()
+            .map(|function| Json::object(vec![
+                ("name", Json::Str(function.name.clone())),
+                ("signature", Json::Str(signature_text(function))),
+                ("instructions", ir_instructions_json(&function.body)),
+            ]))
+            .collect())),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::commands;
+    use crate::parser_core::lexer::Lexer;
+
+    fn ast(source: &str) -> AST {
+        commands::parse(Lexer::from_source("<test>".to_string(), source.to_string()), &EmitOptions::none()).unwrap()
+    }
+
+    #[test]
+    fn prints_ast_as_an_indented_tree() {
+        assert_eq!(ast_pretty(&ast("x = 1 + 2 * y;")), concat!(
+            "set x @1:1-1:14\n",
+            "    Binary + @1:5-1:13\n",
+            "        Noun int 1 @1:5-1:5\n",
+            "        Binary * @1:9-1:13\n",
+            "            Noun int 2 @1:9-1:9\n",
+            "            Noun var y @1:13-1:13",
+        ));
+    }
+
+    #[test]
+    fn prints_ir_as_a_numbered_listing() {
+        assert_eq!(ir_pretty(&ast("x = -(1 + 2) * 3;\nx / 2")), "0000  set      x, ((-(1 + 2)) * 3)\n0001  return   (x / 2)");
+    }
+
+    #[test]
+    fn lists_function_bodies_after_the_program() {
+        let source = "add: int (a: int, b) {\n    a + b\n}\nadd(1, 2 * 3)";
+        assert_eq!(ir_pretty(&ast(source)), concat!(
+            "0000  function add(a: int, b): int\n",
+            "0001  return   add(1, (2 * 3))\n",
+            "\n",
+            "add:\n",
+            "0000  return   (a + b)",
+        ));
+        assert_eq!(ast_pretty(&ast(source)).lines().next(), Some("function add(a: int, b): int @1:1-3:1"));
+    }
+
+    #[test]
+    fn encodes_tokens_as_json() {
+        let mut lexer = Lexer::from_source("<test>".to_string(), "\"hi\"".to_string());
+        lexer.run().unwrap();
+        assert_eq!(tokens_json(&lexer.tokenized_lines).encode(), concat!(
+            r#"{"statements":[{"suffix":"return","span":{"line":1,"column":1,"end_line":1,"end_column":4},"#,
+            r#""tokens":[{"kind":"noun","value":{"type":"str","value":"hi"},"span":{"line":1,"column":1,"end_line":1,"end_column":4}}]}]}"#,
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use crate::diagnostics::emitter::Emitter;
+use crate::driver::commands::{self, FAILURE, SUCCESS};
+use crate::driver::emit::EmitOptions;
+use crate::executer::interpreter::Interpreter;
+use crate::parser_core::ast::AST;
+use crate::parser_core::lexer::Lexer;
+use crate::parser_core::value::Value;
+
+const HISTORY_FILE: &str = ".luma_history";
+
+// The history lives in the home directory when there is one, otherwise next to where the REPL was started
+fn history_path() -> PathBuf {
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(HISTORY_FILE),
+        None => PathBuf::from(HISTORY_FILE),
+    }
+}
+
+// Net number of `{` left open by a line, braces inside strings and comments are ignored
+fn open_braces(line: &str) -> i32 {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(character) = chars.next() {
+        match (quote, character) {
+            (Some(_), '\\') => {
+                chars.next();
+            },
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {},
+            (None, '"') | (None, '\'') => quote = Some(character),
+            (None, '/') if chars.peek() == Some(&'/') => break,
+            (None, '{') => depth += 1,
+            (None, '}') => depth -= 1,
+            _ => {},
+        }
+    }
+
+    depth
+}
+
+pub struct Repl {
+    interpreter: Interpreter,   // Kept for the whole session so variables survive between inputs
+    history: Option<File>,
+    history_path: PathBuf,
+    entries: usize,
+}
+
+impl Repl {
+    pub fn new(strict: bool) -> Self {
+        let history_path = history_path();
+
+        let mut interpreter = Interpreter::new(AST::new(Vec::new()));
+        if strict {
+            interpreter.enable_strict();
+        }
+
+        Repl {
+            interpreter,
+            // A missing or read only history file should not stop the session
+            history: OpenOptions::new().create(true).append(true).open(&history_path).ok(),
+            history_path,
+            entries: 0,
+        }
+    }
+
+    fn record(&mut self, entry: &str) {
+        if let Some(file) = &mut self.history {
+            let _ = writeln!(file, "{}", entry.trim_end());
+        }
+    }
+
+    fn print_history(&self) {
+        match std::fs::read_to_string(&self.history_path) {
+            Ok(contents) => {
+                for (i, line) in contents.lines().enumerate() {
+                    println!("{:>5}  {}", i + 1, line);
+                }
+            },
+            Err(err) => eprintln!("could not read {}: {}", self.history_path.display(), err),
+        }
+    }
+
+    // Read one complete input, continuation lines are requested while a `{` is left open
+    fn read_entry(&self) -> io::Result<Option<String>> {
+        let stdin = io::stdin();
+        let mut entry = String::new();
+        let mut depth = 0;
+
+        loop {
+            print!("{}", if entry.is_empty() { "> " } else { "... " });
+            io::stdout().flush()?;
+
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                return Ok(if entry.is_empty() { None } else { Some(entry) });
+            }
+
+            depth += open_braces(&line);
+            entry += &line;
+
+            if depth <= 0 {
+                return Ok(Some(entry));
+            }
+        }
+    }
+
+    fn evaluate(&mut self, entry: String, emitter: &mut Emitter) {
+        self.entries += 1;
+        let file_name = format!("<repl:{}>", self.entries);
+        emitter.add_source(file_name.clone().into(), entry.clone());
+
+        let ast = match commands::parse(Lexer::from_source(file_name, entry), &EmitOptions::none()) {
+            Ok(val) => val,
+            Err(errors) => {
+                commands::emit_all(emitter, &errors);
+                return;
+            },
+        };
+
+        // Only return style lines produce a value, set statements run silently
+        self.interpreter.load(ast);
+        match self.interpreter.run() {
+            Ok(Value::Undefined) => {},
+            Ok(val) => println!("{}", commands::display(&val)),
+            Err(err) => emitter.emit(&err),
+        }
+    }
+
+    pub fn run(&mut self, emitter: &mut Emitter) -> i32 {
+        println!("Luma REPL, type :history to list previous inputs and :quit to exit");
+
+        loop {
+            let entry = match self.read_entry() {
+                Ok(Some(val)) => val,
+                Ok(None) => return SUCCESS,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return FAILURE;
+                },
+            };
+
+            match entry.trim() {
+                "" => continue,
+                ":quit" | ":exit" => return SUCCESS,
+                ":history" => {
+                    self.print_history();
+                    continue;
+                },
+                _ => {},
+            }
+
+            self.record(&entry);
+            self.evaluate(entry, emitter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_braces_outside_strings_and_comments() {
+        assert_eq!(open_braces("main: int () {"), 1);
+        assert_eq!(open_braces("}"), -1);
+        assert_eq!(open_braces("x = \"{\"; // {"), 0);
+        assert_eq!(open_braces("c = '}';"), 0);
+    }
+}
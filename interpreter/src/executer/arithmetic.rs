@@ -0,0 +1,265 @@
+use num_bigint::BigInt;
+
+use crate::executer::runtime::error::RuntimeError;
+use crate::parser_core::tokenized::Verb;
+use crate::parser_core::value::{CastTo, Value};
+
+// **GOAL:** Give `+ - * /` one defined result for every pair of values, independent of which side a value is written on
+//
+//     int     op int     -> int
+//     long    op int     -> long     (either side, the int is promoted)
+//     bigint  op integer -> bigint   (either side, the int or long is promoted)
+//     integer op float   -> float    (either side, the integer is promoted)
+//     float   op float   -> float
+//     char    +  int     -> char     (either side, moves the code point)
+//     char    -  int     -> char
+//     char    -  char    -> int      (the distance between the code points)
+//     str     +  any     -> str      (either side, the other value is concatenated as text)
+//
+// Every other pair is a type mismatch, a char never silently turns into a number for `*` or `/`
+// Integer arithmetic is checked, leaving the range of its type or dividing by zero is an error rather than a wrap or a panic
+
+// A pair of numbers after promotion to the wider of their two types
+enum Numbers {
+    Ints(i32, i32),
+    Longs(i64, i64),
+    BigInts(BigInt, BigInt),
+    Floats(f64, f64),
+}
+
+// Position in the numeric tower, a pair is promoted to the type of its higher rank
+fn rank(val: &Value) -> Option<u8> {
+    match val {
+        Value::Int(_) => Some(0),
+        Value::Long(_) => Some(1),
+        Value::BigInt(_) => Some(2),
+        Value::Float(_) => Some(3),
+        _ => None,
+    }
+}
+
+fn promote(a: &Value, b: &Value) -> Option<Numbers> {
+    // Widening never fails, except a bigint too large for a float which is left undefined
+    Some(match (rank(a)?).max(rank(b)?) {
+        0 => match (a, b) {
+            (Value::Int(a), Value::Int(b)) => Numbers::Ints(*a, *b),
+            _ => return None,
+        },
+        1 => match (CastTo::<i64>::cast_to(a)?, CastTo::<i64>::cast_to(b)?) {
+            (Value::Long(a), Value::Long(b)) => Numbers::Longs(a, b),
+            _ => return None,
+        },
+        2 => match (CastTo::<BigInt>::cast_to(a)?, CastTo::<BigInt>::cast_to(b)?) {
+            (Value::BigInt(a), Value::BigInt(b)) => Numbers::BigInts(a, b),
+            _ => return None,
+        },
+        _ => match (CastTo::<f64>::cast_to(a)?, CastTo::<f64>::cast_to(b)?) {
+            (Value::Float(a), Value::Float(b)) => Numbers::Floats(a, b),
+            _ => return None,
+        },
+    })
+}
+
+fn shift(verb: &Verb, c: char, offset: i32) -> Result<Value, RuntimeError> {
+    (c as i32).checked_add(offset)
+        .and_then(|code| u32::try_from(code).ok())
+        .and_then(char::from_u32)
+        .map(Value::Char)
+        .ok_or_else(|| RuntimeError::Overflow(verb.clone(), "char".to_string()))
+}
+
+fn text(val: &Value) -> Option<String> {
+    match CastTo::<String>::cast_to(val) {
+        Some(Value::Str(s)) => Some(s),
+        _ => None,
+    }
+}
+
+// Applies a checked integer operation, telling a zero divisor apart from a result outside the type
+fn checked<T: Default + PartialEq>(verb: &Verb, type_name: &str, divisor: &T, result: Option<T>) -> Result<T, RuntimeError> {
+    match result {
+        Some(result) => Ok(result),
+        None if matches!(verb, Verb::Div) && *divisor == T::default() => Err(RuntimeError::DivisionByZero),
+        None => Err(RuntimeError::Overflow(verb.clone(), type_name.to_string())),
+    }
+}
+
+fn mismatch(verb: &Verb, a: &Value, b: &Value) -> RuntimeError {
+    RuntimeError::TypeMismatch(verb.symbol().to_string(), vec![a.type_name(), b.type_name()])
+}
+
+pub fn apply(verb: &Verb, a: Value, b: Value) -> Result<Value, RuntimeError> {
+    if let Some(numbers) = promote(&a, &b) {
+        return Ok(match (verb, numbers) {
+            (Verb::Add, Numbers::Ints(a, b)) => Value::Int(checked(verb, "int", &b, a.checked_add(b))?),
+            (Verb::Sub, Numbers::Ints(a, b)) => Value::Int(checked(verb, "int", &b, a.checked_sub(b))?),
+            (Verb::Mult, Numbers::Ints(a, b)) => Value::Int(checked(verb, "int", &b, a.checked_mul(b))?),
+            (Verb::Div, Numbers::Ints(a, b)) => Value::Int(checked(verb, "int", &b, a.checked_div(b))?),
+            (Verb::Add, Numbers::Longs(a, b)) => Value::Long(checked(verb, "long", &b, a.checked_add(b))?),
+            (Verb::Sub, Numbers::Longs(a, b)) => Value::Long(checked(verb, "long", &b, a.checked_sub(b))?),
+            (Verb::Mult, Numbers::Longs(a, b)) => Value::Long(checked(verb, "long", &b, a.checked_mul(b))?),
+            (Verb::Div, Numbers::Longs(a, b)) => Value::Long(checked(verb, "long", &b, a.checked_div(b))?),
+            (Verb::Add, Numbers::BigInts(a, b)) => Value::BigInt(a + b),
+            (Verb::Sub, Numbers::BigInts(a, b)) => Value::BigInt(a - b),
+            (Verb::Mult, Numbers::BigInts(a, b)) => Value::BigInt(a * b),
+            (Verb::Div, Numbers::BigInts(a, b)) => Value::BigInt(checked(verb, "bigint", &b, a.checked_div(&b))?),
+            (Verb::Add, Numbers::Floats(a, b)) => Value::Float(a + b),
+            (Verb::Sub, Numbers::Floats(a, b)) => Value::Float(a - b),
+            (Verb::Mult, Numbers::Floats(a, b)) => Value::Float(a * b),
+            (Verb::Div, Numbers::Floats(a, b)) => Value::Float(a / b),
+            _ => return Err(mismatch(verb, &a, &b)),
+        });
+    }
+
+    match (verb, &a, &b) {
+        (Verb::Add, Value::Char(c), Value::Int(n)) | (Verb::Add, Value::Int(n), Value::Char(c)) => shift(verb, *c, *n),
+        (Verb::Sub, Value::Char(c), Value::Int(n)) => match n.checked_neg() {
+            Some(n) => shift(verb, *c, n),
+            None => Err(RuntimeError::Overflow(verb.clone(), "char".to_string())),
+        },
+        (Verb::Sub, Value::Char(a), Value::Char(b)) => Ok(Value::Int(*a as i32 - *b as i32)),
+        (Verb::Add, Value::Str(_), _) | (Verb::Add, _, Value::Str(_)) => match (text(&a), text(&b)) {
+            (Some(a), Some(b)) => Ok(Value::Str(a + &b)),
+            _ => Err(mismatch(verb, &a, &b)),
+        },
+        _ => Err(mismatch(verb, &a, &b)),
+    }
+}
+
+// Unary minus, negating the smallest int or long has no result in its type
+pub fn negate(val: Value) -> Result<Value, RuntimeError> {
+    let overflow = |type_name: &str| RuntimeError::Overflow(Verb::Sub, type_name.to_string());
+    match val {
+        Value::Int(n) => n.checked_neg().map(Value::Int).ok_or_else(|| overflow("int")),
+        Value::Long(n) => n.checked_neg().map(Value::Long).ok_or_else(|| overflow("long")),
+        Value::BigInt(n) => Ok(Value::BigInt(-n)),
+        Value::Float(f) => Ok(Value::Float(-f)),
+        other => Err(RuntimeError::TypeMismatch(Verb::Sub.symbol().to_string(), vec![other.type_name()])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::parser_core::ast::{AST, Function};
+    use crate::parser_core::span::Span;
+
+    // One value of every variant, in the same order as `NAMES`
+    fn samples() -> Vec<Value> {
+        let function = Function {
+            name: "f".to_string(),
+            params: Vec::new(),
+            return_type: None,
+            body: AST::new(Vec::new()),
+            span: Span::new(Rc::from("<test>"), 1, 1, 1, 1),
+        };
+
+        vec![
+            Value::Int(6),
+            Value::Long(7),
+            Value::BigInt(BigInt::from(8)),
+            Value::Float(1.5),
+            Value::Str("s".to_string()),
+            Value::Char('c'),
+            Value::Bool(true),
+            Value::List(vec![Value::Int(1)]),
+            Value::Function(Rc::new(function)),
+            Value::VarName("x".to_string()),
+            Value::Undefined,
+        ]
+    }
+
+    const NAMES: [&str; 11] = ["int", "long", "bigint", "float", "str", "char", "bool", "list", "function", "name", "undefined"];
+
+    // The type every defined pair produces, any pair missing from the table must be a type mismatch
+    fn expected(verb: &Verb, a: &str, b: &str) -> Option<&'static str> {
+        match (verb, a, b) {
+            (_, "int", "int") => Some("int"),
+            (_, "int" | "long", "int" | "long") => Some("long"),
+            (_, "int" | "long" | "bigint", "int" | "long" | "bigint") => Some("bigint"),
+            (_, "int" | "long" | "bigint" | "float", "int" | "long" | "bigint" | "float") => Some("float"),
+            (Verb::Add, "char", "int") | (Verb::Add, "int", "char") | (Verb::Sub, "char", "int") => Some("char"),
+            (Verb::Sub, "char", "char") => Some("int"),
+            (Verb::Add, "str", other) | (Verb::Add, other, "str") if other != "undefined" => Some("str"),
+            _ => None,
+        }
+    }
+
+    fn kind(result: &Result<Value, RuntimeError>) -> &'static str {
+        match result {
+            Ok(Value::Int(_)) => "int",
+            Ok(Value::Long(_)) => "long",
+            Ok(Value::BigInt(_)) => "bigint",
+            Ok(Value::Float(_)) => "float",
+            Ok(Value::Str(_)) => "str",
+            Ok(Value::Char(_)) => "char",
+            Ok(Value::Bool(_)) => "bool",
+            Ok(Value::List(_)) => "list",
+            Ok(Value::Function(_)) => "function",
+            Ok(Value::VarName(_)) => "name",
+            Ok(Value::Undefined) => "undefined",
+            Err(RuntimeError::TypeMismatch(_, _)) => "mismatch",
+            Err(_) => "error",
+        }
+    }
+
+    #[test]
+    fn every_pair_of_variants_has_a_defined_result() {
+        for verb in [Verb::Add, Verb::Sub, Verb::Mult, Verb::Div] {
+            for (a, a_name) in samples().into_iter().zip(NAMES) {
+                for (b, b_name) in samples().into_iter().zip(NAMES) {
+                    let result = apply(&verb, a.clone(), b.clone());
+                    assert_eq!(
+                        kind(&result),
+                        expected(&verb, a_name, b_name).unwrap_or("mismatch"),
+                        "{} {} {}", a_name, verb.symbol(), b_name,
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn promotes_ints_on_either_side() {
+        assert!(matches!(apply(&Verb::Add, Value::Int(1), Value::Float(2.5)), Ok(Value::Float(f)) if f == 3.5));
+        assert!(matches!(apply(&Verb::Add, Value::Float(2.5), Value::Int(1)), Ok(Value::Float(f)) if f == 3.5));
+        assert!(matches!(apply(&Verb::Div, Value::Int(7), Value::Float(2.0)), Ok(Value::Float(f)) if f == 3.5));
+        assert!(matches!(apply(&Verb::Div, Value::Int(7), Value::Int(2)), Ok(Value::Int(3))));
+    }
+
+    #[test]
+    fn widens_integers_to_the_larger_type() {
+        assert!(matches!(apply(&Verb::Mult, Value::Int(3), Value::Long(5_000_000_000)), Ok(Value::Long(15_000_000_000))));
+        assert!(matches!(apply(&Verb::Add, Value::Long(1), Value::BigInt(BigInt::from(i64::MAX))), Ok(Value::BigInt(ref n)) if *n == BigInt::from(i64::MAX) + 1));
+        assert!(matches!(apply(&Verb::Div, Value::BigInt(BigInt::from(9)), Value::Float(2.0)), Ok(Value::Float(f)) if f == 4.5));
+    }
+
+    #[test]
+    fn checks_integer_overflow_and_division_by_zero() {
+        assert!(matches!(apply(&Verb::Add, Value::Int(i32::MAX), Value::Int(1)), Err(RuntimeError::Overflow(_, ref t)) if t == "int"));
+        assert!(matches!(apply(&Verb::Mult, Value::Long(i64::MAX), Value::Int(2)), Err(RuntimeError::Overflow(_, ref t)) if t == "long"));
+        assert!(matches!(apply(&Verb::Div, Value::Int(i32::MIN), Value::Int(-1)), Err(RuntimeError::Overflow(_, _))));
+        assert!(matches!(negate(Value::Int(i32::MIN)), Err(RuntimeError::Overflow(_, _))));
+
+        assert!(matches!(apply(&Verb::Div, Value::Int(1), Value::Int(0)), Err(RuntimeError::DivisionByZero)));
+        assert!(matches!(apply(&Verb::Div, Value::Long(1), Value::Int(0)), Err(RuntimeError::DivisionByZero)));
+        assert!(matches!(apply(&Verb::Div, Value::BigInt(BigInt::from(1)), Value::Int(0)), Err(RuntimeError::DivisionByZero)));
+        assert!(matches!(apply(&Verb::Div, Value::Int(1), Value::Float(0.0)), Ok(Value::Float(f)) if f.is_infinite()));
+    }
+
+    #[test]
+    fn char_arithmetic_moves_code_points() {
+        assert!(matches!(apply(&Verb::Add, Value::Char('a'), Value::Int(1)), Ok(Value::Char('b'))));
+        assert!(matches!(apply(&Verb::Add, Value::Int(2), Value::Char('a')), Ok(Value::Char('c'))));
+        assert!(matches!(apply(&Verb::Sub, Value::Char('c'), Value::Char('a')), Ok(Value::Int(2))));
+        assert!(matches!(apply(&Verb::Sub, Value::Char('a'), Value::Int(98)), Err(RuntimeError::Overflow(_, ref t)) if t == "char"));
+    }
+
+    #[test]
+    fn concatenates_strs_on_either_side() {
+        assert!(matches!(apply(&Verb::Add, Value::Str("n = ".to_string()), Value::Int(1)), Ok(Value::Str(ref s)) if s == "n = 1"));
+        assert!(matches!(apply(&Verb::Add, Value::Float(0.5), Value::Str("!".to_string())), Ok(Value::Str(ref s)) if s == "0.5!"));
+    }
+}
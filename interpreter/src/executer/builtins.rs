@@ -0,0 +1,41 @@
+use crate::executer::runtime::error::RuntimeError;
+use crate::parser_core::value::Value;
+
+// Functions provided by the interpreter, a program can shadow one by defining a function with the same name
+// Every builtin can also be called as `#name`, which source can't spell, so code generated by the parser always reaches the builtin
+#[derive(Clone, Debug, PartialEq)]
+pub enum Builtin {
+    Len,
+}
+
+impl Builtin {
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        match name.strip_prefix('#').unwrap_or(name) {
+            "len" => Some(Builtin::Len),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Builtin::Len => "len",
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self {
+            Builtin::Len => 1,
+        }
+    }
+
+    // Arguments are checked against arity() before the call
+    pub fn call(&self, args: &[Value]) -> Result<Value, RuntimeError> {
+        match self {
+            Builtin::Len => match &args[0] {
+                Value::List(items) => Ok(Value::Int(items.len() as i32)),
+                Value::Str(s) => Ok(Value::Int(s.chars().count() as i32)),
+                other => Err(RuntimeError::TypeMismatch(self.name().to_string(), vec![other.type_name()])),
+            },
+        }
+    }
+}
@@ -1,137 +1,779 @@
-use crate::parser_core::ast::{AST, AST_type};
-use crate::parser_core::value::{Value};
-use crate::parser_core::tokenized::{Token, Verb};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use num_traits::Zero;
+
+use crate::parser_core::ast::{AST, AST_type, Expression, Function};
+use crate::parser_core::value::{CastTo, Type, Value};
+use crate::parser_core::span::Span;
+use crate::parser_core::tokenized::{Verb};
 use crate::executer::runtime::environment::{Environment};
+use crate::executer::builtins::Builtin;
+use crate::executer::arithmetic;
+use crate::executer::runtime::error::RuntimeError;
+use crate::diagnostics::diagnostic::Diagnostic;
+
+// Deepest call chain allowed before the program is stopped, keeps runaway recursion from overflowing the Rust stack
+const MAX_CALL_DEPTH: usize = 1000;
+
+// Stack size of the thread running the interpreter, enough for MAX_CALL_DEPTH nested calls in a debug build
+pub const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+// One active function call
+struct Frame {
+    function: String,
+    call_span: Span,
+}
 
 pub struct Interpreter {
-    ast: AST,
-    env: Environment
+    ast: Rc<AST>,
+    env: Rc<RefCell<Environment>>,   // Global environment, every call gets a fresh child of it
+    call_stack: Vec<Frame>,
+    strict: bool,   // Turns operations that would quietly produce undefined into errors
 }
 
 impl Interpreter {
     pub fn new(ast: AST) -> Self {
         Interpreter {
-            ast: ast,
-            env: Environment::new(None),
+            env: Rc::new(RefCell::new(Environment::new(None))),
+            call_stack: Vec::new(),
+            strict: ast.strict,
+            ast: Rc::new(ast),
         }
     }
 
-    fn evaluate_expression(&self, expression: Vec<Token>) -> Value {
-        let mut iter = expression.iter();
+    // Replace the program while keeping every variable defined so far, used by the REPL to run one input at a time
+    // Strict mode stays on once an input has turned it on
+    pub fn load(&mut self, ast: AST) {
+        self.strict |= ast.strict;
+        self.ast = Rc::new(ast);
+    }
 
-        let mut next_token = iter.next();
+    // Strict mode can be turned on from outside the program (`--strict`), but never off
+    pub fn enable_strict(&mut self) {
+        self.strict = true;
+    }
 
-        let mut a = Value::Undefined;
-        let mut opp = Verb::None;
+    // Trailing command line arguments are exposed shell style, `argc` holds the count and `arg1`..`argN` the values
+    pub fn bind_args(&mut self, args: &[String]) {
+        let mut env = self.env.borrow_mut();
+        env.vars.insert("argc".to_string(), Value::Int(args.len() as i32));
+        for (i, arg) in args.iter().enumerate() {
+            env.vars.insert(format!("arg{}", i + 1), Value::Str(arg.clone()));
+        }
+    }
 
-        while let Some(token) = next_token {
-            match token {
-                Token::Noun(val) => {
-                    match a {
-                        Value::Undefined => {
-                            a = match val.clone() {
-                                Value::VarName(name) => self.env.search_for_var(name.to_string()),
-                                _ => val.clone(),
-                            };
-                        }
-                        _ => {
-                            let resolved_val = match val {
-                                Value::VarName(name) => self.env.search_for_var(name.clone()),
-                                _ => val.clone(),
-                            };
-                            
-                            match opp {
-                                Verb::Add => {
-                                    // Cast b to the type of a and perform addition
-                                    let result = resolved_val.cast_to_type(&a);
-                                    a = match (a, result) {
-                                        (Value::Int(a_val), Value::Int(b_val)) => Value::Int(a_val + b_val),
-                                        (Value::Float(a_val), Value::Float(b_val)) => Value::Float(a_val + b_val),
-                                        (Value::Str(a_val), Value::Str(b_val)) => Value::Str(a_val + &b_val),
-                                        (Value::Char(a_val), Value::Char(b_val)) => Value::Int(a_val as i32 + b_val as i32),
-                                        _ => Value::Undefined,
-                                    };
-                                }
-                                Verb::Sub => {
-                                    // Cast b to the type of a and perform subtraction
-                                    let result = resolved_val.cast_to_type(&a);
-                                    a = match (a, result) {
-                                        (Value::Int(a_val), Value::Int(b_val)) => Value::Int(a_val - b_val),
-                                        (Value::Float(a_val), Value::Float(b_val)) => Value::Float(a_val - b_val),
-                                        (Value::Str(a_val), Value::Str(b_val)) => Value::Undefined,
-                                        (Value::Char(a_val), Value::Char(b_val)) => Value::Int(a_val as i32 - b_val as i32),
-                                        _ => Value::Undefined,
-                                    };
-                                }
-                                Verb::Div => {
-                                    // Cast b to the type of a and perform division
-                                    let result = resolved_val.cast_to_type(&a);
-                                    a = match (a, result) {
-                                        (Value::Int(a_val), Value::Int(b_val)) => Value::Int(a_val / b_val),
-                                        (Value::Float(a_val), Value::Float(b_val)) => Value::Float(a_val / b_val),
-                                        (Value::Str(a_val), Value::Str(b_val)) => Value::Undefined,
-                                        (Value::Char(a_val), Value::Char(b_val)) => Value::Int(a_val as i32 / b_val as i32),
-                                        _ => Value::Undefined,
-                                    };
-                                }
-                                Verb::Mult => {
-                                    // Cast b to the type of a and perform multiplication
-                                    let result = resolved_val.cast_to_type(&a);
-                                    a = match (a, result) {
-                                        (Value::Int(a_val), Value::Int(b_val)) => Value::Int(a_val * b_val),
-                                        (Value::Float(a_val), Value::Float(b_val)) => Value::Float(a_val * b_val),
-                                        (Value::Str(a_val), Value::Str(b_val)) => Value::Undefined,
-                                        (Value::Char(a_val), Value::Char(b_val)) => Value::Int(a_val as i32 * b_val as i32),
-                                        _ => Value::Undefined,
-                                    };
-                                }
-                                Verb::None => {
-                                    a = resolved_val.clone();
-                                }
-                                Verb::Set => {
-                                    // Handle setting variables
-                                    if let Value::VarName(name) = &a {
-                                        // TODO: Implement variable setting
-                                        a = resolved_val.clone();
-                                    } else {
-                                        a = Value::Undefined;
-                                    }
-                                }
-                            }
+    fn apply_verb(verb: &Verb, a: Value, b: Value) -> Result<Value, RuntimeError> {
+        // Comparisons look at the values as they are, so `"1" == 1` is false rather than a string comparison
+        // Values that can't be ordered against each other (e.g. a str and an int) compare as false
+        match verb {
+            Verb::Eq => return Ok(Value::Bool(a == b)),
+            Verb::NotEq => return Ok(Value::Bool(a != b)),
+            Verb::Less => return Ok(Value::Bool(a < b)),
+            Verb::LessEq => return Ok(Value::Bool(a <= b)),
+            Verb::Greater => return Ok(Value::Bool(a > b)),
+            Verb::GreaterEq => return Ok(Value::Bool(a >= b)),
+            Verb::Xor => return Ok(Value::Bool(Self::is_truthy(&a) != Self::is_truthy(&b))),
+            _ => {},
+        }
+
+        arithmetic::apply(verb, a, b)
+    }
+
+    // Attach the location of the failing expression to a runtime error
+    // Outside strict mode the lenient failures (a type mismatch, a missing variable, an index out of range) produce undefined
+    fn locate(&self, result: Result<Value, RuntimeError>, span: &Span) -> Result<Value, Diagnostic> {
+        match result {
+            Ok(val) => Ok(val),
+            Err(error) if error.is_lenient() && !self.strict => Ok(Value::Undefined),
+            Err(error) if error.is_lenient() => Err(error.at(span).with_note("strict mode is on, outside of it this produces `undefined`".to_string())),
+            Err(error) => Err(error.at(span)),
+        }
+    }
+
+    // Cast a value assigned to a typed variable to its declared type, values the cast machinery can't convert are rejected
+    fn coerce(val: Value, declared_type: &Type, name: &str, span: &Span) -> Result<Value, Diagnostic> {
+        match val.cast_to_type(&declared_type.template()) {
+            Value::Undefined => Err(
+                Diagnostic::error("E0402", format!("mismatched types: cannot assign a `{}` value to `{}`", val.type_name(), name), Some(span.clone()))
+                    .with_note(format!("`{}` is declared as `{}`", name, declared_type.name())),
+            ),
+            casted => Ok(casted),
+        }
+    }
+
+    fn evaluate_expression(&mut self, expression: &Expression, env: &Rc<RefCell<Environment>>) -> Result<Value, Diagnostic> {
+        Ok(match expression {
+            Expression::Noun(val, span) => match val {
+                Value::VarName(name) => {
+                    let found = env.borrow().lookup(name);
+                    self.locate(found.ok_or_else(|| RuntimeError::UndefinedVariable(name.clone())), span)?
+                },
+                _ => val.clone(),
+            },
+            Expression::Unary(verb, operand, span) => {
+                let val = self.evaluate_expression(operand, env)?;
+                match (verb, val) {
+                    (Verb::Sub, val) => self.locate(arithmetic::negate(val), span)?,
+                    (Verb::Not, val) => Value::Bool(!Self::is_truthy(&val)),
+                    _ => Value::Undefined,
+                }
+            },
+            // `and` and `or` only evaluate the right side when the left doesn't already decide the result
+            Expression::Binary(Verb::And, left, right, _) => {
+                let a = self.evaluate_expression(left, env)?;
+                Value::Bool(Self::is_truthy(&a) && Self::is_truthy(&self.evaluate_expression(right, env)?))
+            },
+            Expression::Binary(Verb::Or, left, right, _) => {
+                let a = self.evaluate_expression(left, env)?;
+                Value::Bool(Self::is_truthy(&a) || Self::is_truthy(&self.evaluate_expression(right, env)?))
+            },
+            Expression::Binary(verb, left, right, span) => {
+                // Operands are evaluated left to right before the verb is applied
+                let a = self.evaluate_expression(left, env)?;
+                let b = self.evaluate_expression(right, env)?;
+                self.locate(Self::apply_verb(verb, a, b), span)?
+            },
+            Expression::Call(name, args, span) => {
+                let callee = env.borrow().search_for_var(name.clone());
+
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(self.evaluate_expression(arg, env)?);
+                }
+
+                match (callee, Builtin::from_name(name)) {
+                    (Value::Function(function), _) => self.call(&function, values, span)?,
+                    (Value::Undefined, Some(builtin)) => {
+                        if values.len() != builtin.arity() {
+                            return Err(Diagnostic::error("E0404", format!("`{}` takes {} argument(s) but {} were supplied", builtin.name(), builtin.arity(), values.len()), Some(span.clone())));
                         }
-                    }
+                        self.locate(builtin.call(&values), span)?
+                    },
+                    (Value::Undefined, None) => return Err(RuntimeError::UndefinedVariable(name.clone()).at(span)),
+                    (other, _) => return Err(Diagnostic::error("E0403", format!("`{}` is not a function", name), Some(span.clone()))
+                        .with_note(format!("`{}` holds a `{}` value", name, other.type_name()))),
                 }
-                Token::Verb(val) => {
-                    opp = val.clone();
+            },
+            Expression::List(items, _) => {
+                let mut values = Vec::new();
+                for item in items {
+                    values.push(self.evaluate_expression(item, env)?);
                 }
+                Value::List(values)
+            },
+            Expression::Index(list, index, span) => {
+                let list = self.evaluate_expression(list, env)?;
+                let index = self.evaluate_expression(index, env)?;
+
+                // Negative indexes are out of range like any other index past the end
+                let item = match (&list, &index) {
+                    (Value::List(items), Value::Int(i)) => usize::try_from(*i).ok().and_then(|position| items.get(position).cloned())
+                        .ok_or(RuntimeError::IndexOutOfRange(*i as i64, items.len())),
+                    (Value::Str(s), Value::Int(i)) => usize::try_from(*i).ok().and_then(|position| s.chars().nth(position)).map(Value::Char)
+                        .ok_or(RuntimeError::IndexOutOfRange(*i as i64, s.chars().count())),
+                    _ => Err(RuntimeError::TypeMismatch("[]".to_string(), vec![list.type_name(), index.type_name()])),
+                };
+                self.locate(item, span)?
+            },
+            Expression::Cast(operand, target, span) => {
+                let val = self.evaluate_expression(operand, env)?;
+                match (val.cast_to_type(&target.template()), CastTo::<String>::cast_to(&val)) {
+                    (Value::Undefined, Some(Value::Str(text))) => return Err(RuntimeError::InvalidCast(val.type_name(), target.name().to_string()).at(span)
+                        .with_note(format!("the value is `{}`", text))),
+                    (Value::Undefined, _) => return Err(RuntimeError::InvalidCast(val.type_name(), target.name().to_string()).at(span)),
+                    (casted, _) => casted,
+                }
+            },
+            // Parts are rendered like `x:str`, a part that has no text (undefined) makes the whole string undefined
+            Expression::Concat(parts, _) => {
+                let mut text = String::new();
+                for part in parts {
+                    let val = self.evaluate_expression(part, env)?;
+                    match CastTo::<String>::cast_to(&val) {
+                        Some(Value::Str(rendered)) => text += &rendered,
+                        _ => return self.locate(Err(RuntimeError::TypeMismatch("{}".to_string(), vec![val.type_name()])), part.span()),
+                    }
+                }
+                Value::Str(text)
+            },
+        })
+    }
+
+    // Run a function body in a fresh child of the global environment with the parameters bound to the arguments
+    fn call(&mut self, function: &Rc<Function>, args: Vec<Value>, span: &Span) -> Result<Value, Diagnostic> {
+        if args.len() != function.params.len() {
+            return Err(Diagnostic::error("E0404", format!("`{}` takes {} argument(s) but {} were supplied", function.name, function.params.len(), args.len()), Some(span.clone()))
+                .with_note(format!("`{}` is declared at {}", function.name, function.span)));
+        }
+
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            return Err(Diagnostic::error("E0405", format!("call stack overflow while calling `{}`", function.name), Some(span.clone()))
+                .with_note(format!("calls may be nested at most {} deep", MAX_CALL_DEPTH)));
+        }
+
+        let mut call_env = Environment::new(Some(self.env.clone()));
+        for (param, arg) in function.params.iter().zip(args) {
+            let arg = match &param.param_type {
+                Some(param_type) => match arg.cast_to_type(&param_type.template()) {
+                    Value::Undefined => return Err(
+                        Diagnostic::error("E0406", format!("mismatched types: cannot pass a `{}` value as `{}`", arg.type_name(), param.name), Some(span.clone()))
+                            .with_note(format!("parameter `{}` of `{}` is declared as `{}`", param.name, function.name, param_type.name())),
+                    ),
+                    casted => {
+                        call_env.types.insert(param.name.clone(), param_type.clone());
+                        casted
+                    },
+                },
+                None => arg,
+            };
+            call_env.vars.insert(param.name.clone(), arg);
+        }
+
+        self.call_stack.push(Frame { function: function.name.clone(), call_span: span.clone() });
+        let result = self.execute(&function.body, &Rc::new(RefCell::new(call_env)));
+        let frame = self.call_stack.pop().expect("call stack is pushed before the body runs");
+
+        // Errors raised inside the body get one note per frame they unwind through, forming a backtrace
+        // Repeated frames (recursion through the same call site) are only listed once
+        let val = result.map_err(|err| {
+            let note = format!("in `{}` called at {}", frame.function, frame.call_span);
+            match err.notes.last() {
+                Some(last) if last.message == note => err,
+                _ => err.with_note(note),
             }
+        })?;
 
-            next_token = iter.next();
+        match (&function.return_type, val) {
+            // A body that finishes without a return statement produces undefined whatever its declared type
+            (_, None) => Ok(Value::Undefined),
+            (Some(return_type), Some(val)) => match val.cast_to_type(&return_type.template()) {
+                Value::Undefined => Err(
+                    Diagnostic::error("E0407", format!("mismatched types: `{}` returned a `{}` value", function.name, val.type_name()), Some(span.clone()))
+                        .with_note(format!("`{}` is declared to return `{}`", function.name, return_type.name())),
+                ),
+                casted => Ok(casted),
+            },
+            (None, Some(val)) => Ok(val),
         }
+    }
 
-        a
+    // Whether a jump condition holds: nonzero numbers, non empty strings and lists, non null chars and functions, never undefined
+    fn is_truthy(val: &Value) -> bool {
+        match val {
+            Value::Int(n) => *n != 0,
+            Value::Long(n) => *n != 0,
+            Value::BigInt(n) => !n.is_zero(),
+            Value::Float(f) => *f != 0.0,
+            Value::Str(s) => !s.is_empty(),
+            Value::Char(c) => *c != '\0',
+            Value::Bool(b) => *b,
+            Value::Function(_) => true,
+            Value::List(items) => !items.is_empty(),
+            Value::VarName(_) | Value::Undefined => false,
+        }
     }
 
-    pub fn run(&mut self) -> Value {
-        for line in &self.ast.statements {
-            match line.statement_type {
+    // Run statements in order until one returns, None when the end is reached without a return
+    fn execute(&mut self, ast: &AST, env: &Rc<RefCell<Environment>>) -> Result<Option<Value>, Diagnostic> {
+        let mut index = 0;
+
+        while let Some(line) = ast.statements.get(index) {
+            index += 1;
+
+            match &line.statement_type {
                 AST_type::Set => {
                     let var_name = match &line.a {
                         Value::VarName(name) => name,
-                        _ => panic!("Non variable name")
+                        _ => return Err(Diagnostic::error("E0401", "cannot assign to a value that is not a variable".to_string(), Some(line.span.clone())))
                     };
-                    let expression = &line.b;
 
-                    self.env.vars.insert(var_name.clone(), self.evaluate_expression(expression.clone()));
+                    let mut val = self.evaluate_expression(&line.b, env)?;
+
+                    // A declaration replaces the variable's type, plain assignments keep the type it was declared with
+                    let mut env = env.borrow_mut();
+                    if let Some(declared_type) = &line.declared_type {
+                        env.types.insert(var_name.clone(), declared_type.clone());
+                    }
+                    if let Some(declared_type) = env.types.get(var_name) {
+                        val = Self::coerce(val, declared_type, var_name, &line.span)?;
+                    }
+
+                    env.vars.insert(var_name.clone(), val);
+                },
+                AST_type::Function => {
+                    if let Value::VarName(name) = &line.a {
+                        let function = self.evaluate_expression(&line.b, env)?;
+                        env.borrow_mut().vars.insert(name.clone(), function);
+                    }
                 },
                 AST_type::Return => {
-                    let val = &line.b; 
-                    return self.evaluate_expression(val.clone());
-                }
-                _ => panic!("Unknown opperation"),
+                    return Ok(Some(self.evaluate_expression(&line.b, env)?));
+                },
+                AST_type::Mark => {},
+                AST_type::Unset => {
+                    if let Value::VarName(name) = &line.a {
+                        let mut env = env.borrow_mut();
+                        env.vars.remove(name);
+                        env.types.remove(name);
+                    }
+                },
+                AST_type::Do(lookup) => {
+                    if Self::is_truthy(&self.evaluate_expression(&line.b, env)?) {
+                        let name = match &line.a {
+                            Value::VarName(name) => name,
+                            _ => unreachable!("the parser only builds jumps to named markers"),
+                        };
+
+                        // Targets are checked by the parser, so a missing one means the AST was built by hand
+                        index = ast.jump_target(name, lookup, index - 1).ok_or_else(|| {
+                            Diagnostic::error("E0410", format!("no marker `{}` to jump to", name), Some(line.span.clone()))
+                        })?;
+                    }
+                },
             };
         }
 
-        Value::Undefined
+        Ok(None)
+    }
+
+    pub fn run(&mut self) -> Result<Value, Diagnostic> {
+        let ast = self.ast.clone();
+        let env = self.env.clone();
+        Ok(self.execute(&ast, &env)?.unwrap_or(Value::Undefined))
+    }
+
+    // Call the program's `main` function once the top level has run, every process argument is converted to the type of its parameter
+    // Returns None when the program doesn't define `main`
+    pub fn run_main(&mut self, args: &[String]) -> Result<Option<Value>, Diagnostic> {
+        let main = match self.env.borrow().search_for_var("main".to_string()) {
+            Value::Function(function) => function,
+            _ => return Ok(None),
+        };
+
+        if args.len() != main.params.len() {
+            let params: Vec<&str> = main.params.iter().map(|param| param.name.as_str()).collect();
+            return Err(Diagnostic::error("E0408", format!("`main` takes {} argument(s) but {} were given on the command line", main.params.len(), args.len()), Some(main.span.clone()))
+                .with_help(format!("pass the arguments after the script path: luma run <file> {}", params.join(" "))));
+        }
+
+        let mut values = Vec::new();
+        for (param, arg) in main.params.iter().zip(args) {
+            let val = Value::Str(arg.clone());
+            values.push(match &param.param_type {
+                Some(param_type) => match val.cast_to_type(&param_type.template()) {
+                    Value::Undefined => return Err(
+                        Diagnostic::error("E0409", format!("invalid argument `{}` for parameter `{}` of `main`", arg, param.name), Some(main.span.clone()))
+                            .with_note(format!("`{}` is declared as `{}`", param.name, param_type.name())),
+                    ),
+                    casted => casted,
+                },
+                None => val,
+            });
+        }
+
+        // The returned `int` becomes the exit code, the OS would keep only its low byte so 256 would look like success
+        match self.call(&main, values, &main.span)? {
+            Value::Int(code) if !(0..=255).contains(&code) => Err(
+                Diagnostic::error("E0417", format!("`main` returned `{}`, which is not a valid exit code", code), Some(main.span.clone()))
+                    .with_help("return an `int` between 0 and 255".to_string()),
+            ),
+            val => Ok(Some(val)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser_core::lexer::Lexer;
+    use crate::parser_core::parser::Parser;
+
+    fn try_run_source(source: &str) -> Result<Value, Diagnostic> {
+        let mut lexer = Lexer::from_source("<test>".to_string(), source.to_string());
+        lexer.run().unwrap();
+        Interpreter::new(Parser::new(lexer).run().unwrap()).run()
+    }
+
+    fn run_main_source(source: &str, args: &[&str]) -> Result<Option<Value>, Diagnostic> {
+        let mut lexer = Lexer::from_source("<test>".to_string(), source.to_string());
+        lexer.run().unwrap();
+        let mut interpreter = Interpreter::new(Parser::new(lexer).run().unwrap());
+        interpreter.run()?;
+        interpreter.run_main(&args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>())
+    }
+
+    fn run_source(source: &str) -> Value {
+        try_run_source(source).unwrap()
+    }
+
+    #[test]
+    fn respects_operator_precedence() {
+        assert!(matches!(run_source("5 + 2 * 3"), Value::Int(11)));
+        assert!(matches!(run_source("10 - 4 - 3"), Value::Int(3)));
+        assert!(matches!(run_source("24 / 4 / 2"), Value::Int(3)));
+        assert!(matches!(run_source("1 - 2 * 3 + 4"), Value::Int(-1)));
+    }
+
+    #[test]
+    fn evaluates_grouping() {
+        assert!(matches!(run_source("(5 + 2) * 3"), Value::Int(21)));
+        assert!(matches!(run_source("10 - (4 - 3)"), Value::Int(9)));
+    }
+
+    #[test]
+    fn evaluates_nested_grouping() {
+        assert!(matches!(run_source("((2 + 3) * (1 + (6 - 2))) / 5"), Value::Int(5)));
+        assert!(matches!(run_source("((((((1 + 1))))))"), Value::Int(2)));
+    }
+
+    #[test]
+    fn evaluates_unary_minus() {
+        assert!(matches!(run_source("-5"), Value::Int(-5)));
+        assert!(matches!(run_source("x = 4;\n-x * 2"), Value::Int(-8)));
+        assert!(matches!(run_source("3 - -2"), Value::Int(5)));
+        assert!(matches!(run_source("--7"), Value::Int(7)));
+    }
+
+    #[test]
+    fn negates_grouped_expressions() {
+        assert!(matches!(run_source("-(2 + 3) * 2"), Value::Int(-10)));
+        assert!(matches!(run_source("-(1.5)"), Value::Float(f) if f == -1.5));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn promotes_mixed_arithmetic_to_float() {
+        assert!(matches!(run_source("1 + 2.5"), Value::Float(f) if f == 3.5));
+        assert!(matches!(run_source("2.5 + 1"), Value::Float(f) if f == 3.5));
+        assert!(matches!(run_source("x: int = 1 + 2.5;\nx"), Value::Int(3)));
+    }
+
+    #[test]
+    fn reads_long_and_bigint_literals() {
+        assert!(matches!(run_source("5L * 3"), Value::Long(15)));
+        assert!(matches!(run_source("3000000000"), Value::Long(3_000_000_000)));
+        assert!(matches!(run_source("99999999999999999999n + 1"), Value::BigInt(ref n) if n.to_string() == "100000000000000000000"));
+        assert!(matches!(run_source("2n == 2.0 and 3L > 2"), Value::Bool(true)));
+    }
+
+    #[test]
+    fn keeps_whole_floats_as_floats() {
+        assert!(matches!(run_source("3.0"), Value::Float(f) if f == 3.0));
+        assert!(matches!(run_source("1e10 / 4"), Value::Float(f) if f == 2.5e9));
+        assert!(matches!(run_source("3.0:str"), Value::Str(ref s) if s == "3.0"));
+        assert!(matches!(run_source("0xff - 1_000"), Value::Int(-745)));
+    }
+
+    #[test]
+    fn casts_between_integer_types() {
+        assert!(matches!(run_source("x: long = 7;\nx"), Value::Long(7)));
+        assert!(matches!(run_source("x: bigint = \"123456789012345678901234567890\";\nx * 0"), Value::BigInt(ref n) if n.is_zero()));
+        assert!(matches!(run_source("x: int = 5L;\nx"), Value::Int(5)));
+
+        // Narrowing a value that doesn't fit is rejected instead of wrapping
+        let error = try_run_source("x: int = 3000000000;").unwrap_err();
+        assert_eq!(error.code, "E0402");
+        assert_eq!(try_run_source("x: long = 1e30;").unwrap_err().code, "E0402");
+        assert_eq!(try_run_source("1e30:long").unwrap_err().code, "E0416");
+        assert!(matches!(run_source("x: bigint = 1e30;\nx > 0"), Value::Bool(true)));
+    }
+
+    #[test]
+    fn reports_arithmetic_failures_at_the_operation() {
+        let error = try_run_source("x = 0;\ny = 10 / x;").unwrap_err();
+        assert_eq!(error.code, "E0411");
+        assert_eq!((error.span.as_ref().unwrap().line, error.span.as_ref().unwrap().column), (2, 5));
+
+        assert_eq!(try_run_source("2147483647 + 1").unwrap_err().code, "E0412");
+        assert_eq!(try_run_source("x = 2L;\nx * 9223372036854775807L").unwrap_err().code, "E0412");
+        assert!(matches!(run_source("2147483647L + 1"), Value::Long(2_147_483_648)));
+
+        // Mismatched operands still produce undefined
+        assert!(matches!(run_source("\"a\" - \"b\""), Value::Undefined));
+    }
+
+    #[test]
+    fn strict_mode_rejects_implicit_undefined() {
+        let sources = [
+            ("\"a\" - \"b\"", "E0413"),
+            ("x + 1", "E0414"),
+            ("xs = [1, 2];\nxs[2]", "E0415"),
+            ("\"abc\"[-1]", "E0415"),
+            ("len(5)", "E0413"),
+        ];
+
+        for (source, code) in sources {
+            assert!(matches!(run_source(source), Value::Undefined), "{}", source);
+
+            let error = try_run_source(&format!("#strict\n{}", source)).unwrap_err();
+            assert_eq!(error.code, code, "{}", source);
+            assert_eq!(error.notes.len(), 1);
+        }
+
+        assert_eq!(try_run_source("#strict\nx = 1;\ny").unwrap_err().message, "cannot find `y` in this scope");
+        assert!(matches!(run_source("#strict\nx = 2;\nx * 3"), Value::Int(6)));
+    }
+
+    #[test]
+    fn evaluates_cast_expressions() {
+        assert!(matches!(run_source("\"42\":int + 1"), Value::Int(43)));
+        assert!(matches!(run_source("3.9:int"), Value::Int(3)));
+        assert!(matches!(run_source("65:char"), Value::Char('A')));
+        assert!(matches!(run_source("1:str + 1"), Value::Str(ref s) if s == "11"));
+        assert!(matches!(run_source("x = 5;\nx:float / 2"), Value::Float(f) if f == 2.5));
+        assert!(matches!(run_source("age = \"30\";\nage:int < 120:int"), Value::Bool(true)));
+    }
+
+    #[test]
+    fn reports_casts_that_fail() {
+        let error = try_run_source("x = \"abc\";\ny = x:int;").unwrap_err();
+        assert_eq!(error.code, "E0416");
+        assert_eq!(error.message, "cannot cast a `str` value to `int`");
+        assert_eq!(error.notes[0].message, "the value is `abc`");
+
+        assert_eq!(try_run_source("[1]:float").unwrap_err().code, "E0416");
+        assert_eq!(try_run_source("5000000000:int").unwrap_err().code, "E0416");
+    }
+
+    #[test]
+    fn float_casts_fail_when_the_value_does_not_fit() {
+        assert!(matches!(run_source("2147483647.9:int"), Value::Int(2147483647)));
+        assert_eq!(try_run_source("1e10:int").unwrap_err().code, "E0416");
+        assert_eq!(try_run_source("(0.0 / 0.0):int").unwrap_err().code, "E0416");
+        assert_eq!(try_run_source("(1.0 / 0.0):int").unwrap_err().code, "E0416");
+        assert_eq!(try_run_source("x: int = 1e10;").unwrap_err().code, "E0402");
+    }
+
+    #[test]
+    fn float_casts_to_char_fail_when_the_value_is_not_a_code_point() {
+        assert!(matches!(run_source("65.9:char"), Value::Char('A')));
+        assert_eq!(try_run_source("(0.0 / 0.0):char").unwrap_err().code, "E0416");
+        assert_eq!(try_run_source("(-5.5):char").unwrap_err().code, "E0416");
+        assert_eq!(try_run_source("1e10:char").unwrap_err().code, "E0416");
+        assert_eq!(try_run_source("c: char = 55296.0;").unwrap_err().code, "E0402");
+    }
+
+    #[test]
+    fn renders_interpolated_strings() {
+        assert!(matches!(run_source("name = \"Ada\";\nage = 36;\n\"Hello {name}, you are {age + 1}\""), Value::Str(ref s) if s == "Hello Ada, you are 37"));
+        assert!(matches!(run_source("xs = [1, 2.5, 'c'];\n\"{xs} {len(xs) > 2} {\"in {xs[0]}\"}\""), Value::Str(ref s) if s == "[1, 2.5, c] true in 1"));
+        assert!(matches!(run_source("\"\\{x\\} {1}\""), Value::Str(ref s) if s == "{x} 1"));
+
+        // A part with no text makes the string undefined, or an error in strict mode
+        assert!(matches!(run_source("\"a {missing}\""), Value::Undefined));
+        assert_eq!(try_run_source("#strict\n\"a {missing}\"").unwrap_err().code, "E0414");
+    }
+
+    #[test]
+    fn runs_statements_spread_over_lines() {
+        let source = "add (a: int, b: int) { a + b }\nx = 1; y = 2;\ntotal = add(\n    x,\n    y\n) * 10\n    + 5;\nif total > 30 { total = 0; }\ntotal";
+        assert!(matches!(run_source(source), Value::Int(0)));
+    }
+
+    #[test]
+    fn reports_calls_to_unknown_names() {
+        let error = try_run_source("y = 1;\nmissing(y)").unwrap_err();
+        assert_eq!(error.code, "E0414");
+        assert_eq!(error.message, "cannot find `missing` in this scope");
+    }
+
+    #[test]
+    fn coerces_values_to_the_declared_type() {
+        assert!(matches!(run_source("x: float = 10;\nx"), Value::Float(f) if f == 10.0));
+        assert!(matches!(run_source("x: int = 7.9;\nx"), Value::Int(7)));
+        assert!(matches!(run_source("x: str = 5 * 2;\nx"), Value::Str(ref s) if s == "10"));
+        assert!(matches!(run_source("c: char = \"a\";\nc"), Value::Char('a')));
+        assert!(matches!(run_source("c: char = \"é\";\nc"), Value::Char('é')));
+        assert!(matches!(run_source("\"日\":char:str:char"), Value::Char('日')));
+    }
+
+    #[test]
+    fn keeps_the_declared_type_on_reassignment() {
+        assert!(matches!(run_source("x: float = 1;\nx = 2;\nx"), Value::Float(f) if f == 2.0));
+        assert!(matches!(run_source("x: float = 1;\nx: int = 2.5;\nx"), Value::Int(2)));
+    }
+
+    #[test]
+    fn rejects_values_that_cannot_be_cast() {
+        let err = try_run_source("x: int = \"abc\";").unwrap_err();
+        assert_eq!(err.code, "E0402");
+
+        let err = try_run_source("x: char = 1;\nx = \"too long\";").unwrap_err();
+        assert_eq!((err.code, err.span.unwrap().line), ("E0402", 2));
+    }
+
+    #[test]
+    fn calls_functions_with_typed_parameters() {
+        let source = "add: int (a: int, b: int) {\n    c = a + b;\n    c\n}\nadd(2, 3) * add(1.9, 1)";
+        assert!(matches!(run_source(source), Value::Int(10)));
+        assert!(matches!(run_source("half: float (x: float) {\n    x / 2\n}\nhalf(3)"), Value::Float(f) if f == 1.5));
+        assert!(matches!(run_source("nothing () {\n    x = 1;\n}\nnothing()"), Value::Undefined));
+    }
+
+    #[test]
+    fn calls_run_in_their_own_environment() {
+        let source = "x = 1;\nf (x) {\n    y = x * 10;\n    y + g\n}\ng = 5;\nf(2) + y";
+        // y is local to the call so the outer read is undefined, g is a global visible from the body
+        assert!(matches!(run_source(source), Value::Undefined));
+        assert!(matches!(run_source("x = 1;\nf (x) {\n    x = 3;\n    g + x\n}\ng = 5;\nf(2) + x"), Value::Int(9)));
+    }
+
+    #[test]
+    fn supports_recursion() {
+        let source = "fact: int (n: int) {\n    recurse if n - 1?\n    1\n    recurse!\n    fact(n - 1) * n\n}\nfact(5)";
+        assert!(matches!(run_source(source), Value::Int(120)));
+
+        let source = "fact: int (n: int) {\n    m = n - 1;\n    r = fact(m) * n;\n    r\n}\nfact(3)";
+        // Recursion without a base case is reported by the depth limit instead of crashing
+        let (code, notes) = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(move || {
+                let err = try_run_source(source).unwrap_err();
+                (err.code, err.notes.len())
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        // The depth note, the recursive call site once, then the outermost call
+        assert_eq!((code, notes), ("E0405", 3));
+    }
+
+    #[test]
+    fn checks_arity_and_argument_types() {
+        let source = "f: int (a: int, b: int) {\n    a + b\n}\n";
+        assert_eq!(try_run_source(&format!("{}f(1)", source)).unwrap_err().code, "E0404");
+        assert_eq!(try_run_source(&format!("{}f(1, \"x\")", source)).unwrap_err().code, "E0406");
+        assert_eq!(try_run_source("f: int () {\n    \"x\"\n}\nf()").unwrap_err().code, "E0407");
+        assert_eq!(try_run_source("x = 1;\nx(2)").unwrap_err().code, "E0403");
+    }
+
+    #[test]
+    fn converts_process_arguments_for_main() {
+        let source = "main: int (n: int, scale: float, name) {\n    n * 2\n}";
+        assert!(matches!(run_main_source(source, &["21", "0.5", "x"]), Ok(Some(Value::Int(42)))));
+        assert!(matches!(run_main_source("main () {\n    x = 1;\n}", &[]), Ok(Some(Value::Undefined))));
+        assert!(matches!(run_main_source("x = 1;", &["1"]), Ok(None)));
+    }
+
+    #[test]
+    fn rejects_arguments_that_do_not_fit_main() {
+        let source = "main: int (n: int) {\n    n\n}";
+        assert_eq!(run_main_source(source, &[]).unwrap_err().code, "E0408");
+        assert_eq!(run_main_source(source, &["1", "2"]).unwrap_err().code, "E0408");
+        assert_eq!(run_main_source(source, &["1.5"]).unwrap_err().code, "E0409");
+    }
+
+    #[test]
+    fn rejects_exit_codes_outside_a_byte() {
+        let source = "main: int (n: int) {\n    n\n}";
+        assert!(matches!(run_main_source(source, &["255"]), Ok(Some(Value::Int(255)))));
+        assert_eq!(run_main_source(source, &["256"]).unwrap_err().code, "E0417");
+        assert_eq!(run_main_source(source, &["-1"]).unwrap_err().code, "E0417");
+    }
+
+    #[test]
+    fn jumps_forward_over_statements() {
+        assert!(matches!(run_source("x = 1;\nskip if x?\nx = 2;\nskip!\nx"), Value::Int(1)));
+        assert!(matches!(run_source("x = 0;\nskip if x?\nx = 2;\nskip!\nx"), Value::Int(2)));
+        assert!(matches!(run_source("x = 1;\nend?\nx = 2;\nend!\nx"), Value::Int(1)));
+    }
+
+    #[test]
+    fn loops_by_jumping_back() {
+        let source = "i = 0;\ntotal = 0;\nloop!\ntotal = total + i;\ni = i + 1;\n~loop if 5 - i?\ntotal";
+        assert!(matches!(run_source(source), Value::Int(10)));
+    }
+
+    #[test]
+    fn star_jumps_to_the_first_marker_in_the_block() {
+        // `~m` would land on the second marker and loop forever, `*m` restarts from the first one
+        let source = "x = 0;\nm!\nx = x + 1;\nm!\n*m if 3 - x?\nx";
+        assert!(matches!(run_source(source), Value::Int(3)));
+    }
+
+    #[test]
+    fn runs_if_else_chains() {
+        let source = "pick (n) {\n    if n {\n        r = \"some\";\n    } else if 0 {\n        r = \"never\";\n    } else {\n        r = \"none\";\n    }\n    r\n}\npick(1) + pick(0)";
+        assert!(matches!(run_source(source), Value::Str(ref s) if s == "somenone"));
+        assert!(matches!(run_source("x = 1;\nif 0 {\n    x = 2;\n}\nx"), Value::Int(1)));
+    }
+
+    #[test]
+    fn runs_while_loops() {
+        let source = "i = 3;\ntotal = 0;\nwhile i {\n    total = total + i;\n    i = i - 1;\n}\ntotal";
+        assert!(matches!(run_source(source), Value::Int(6)));
+    }
+
+    #[test]
+    fn runs_for_loops_over_lists_and_strs() {
+        assert!(matches!(run_source("total = 0;\nfor x in [1, 2, 3] {\n    total = total + x;\n}\ntotal"), Value::Int(6)));
+        assert!(matches!(run_source("n = 0;\nfor c in \"abc\" {\n    n = n + 1;\n}\nn"), Value::Int(3)));
+
+        // Nested loops get their own generated markers and counters
+        let source = "n = 0;\nfor a in [1, 2] {\n    for b in [1, 2, 3] {\n        n = n + a * b;\n    }\n}\nn";
+        assert!(matches!(run_source(source), Value::Int(18)));
+    }
+
+    #[test]
+    fn for_loops_remove_their_hidden_variables() {
+        let source = "n = 0;\nfor a in [1, 2] {\n    for b in \"xy\" {\n        n = n + 1;\n    }\n}\nn";
+        let mut lexer = Lexer::from_source("<test>".to_string(), source.to_string());
+        lexer.run().unwrap();
+        let mut interpreter = Interpreter::new(Parser::new(lexer).run().unwrap());
+
+        assert!(matches!(interpreter.run(), Ok(Value::Int(4))));
+        let env = interpreter.env.borrow();
+        let mut names: Vec<&str> = env.vars.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, ["a", "b", "n"]);
+        assert!(env.types.is_empty());
+    }
+
+    #[test]
+    fn loops_keep_working_when_len_is_shadowed() {
+        let source = "len (x) {\n    0\n}\nn = 0;\nfor x in [5, 5] {\n    n = n + x;\n}\nn + len([1])";
+        assert!(matches!(run_source(source), Value::Int(10)));
+        assert!(matches!(run_source("len([1, [2, 3]]) + len(\"four\")"), Value::Int(6)));
+    }
+
+    #[test]
+    fn indexes_lists_and_strs() {
+        assert!(matches!(run_source("xs = [10, 20, 30];\nxs[1] + xs[2]"), Value::Int(50)));
+        assert!(matches!(run_source("\"abc\"[2]"), Value::Char('c')));
+        assert!(matches!(run_source("[1][5]"), Value::Undefined));
+    }
+
+    #[test]
+    fn compares_values() {
+        assert!(matches!(run_source("1 < 2"), Value::Bool(true)));
+        assert!(matches!(run_source("2 >= 2.5"), Value::Bool(false)));
+        assert!(matches!(run_source("2 == 2.0"), Value::Bool(true)));
+        assert!(matches!(run_source("\"abc\" < \"abd\""), Value::Bool(true)));
+        assert!(matches!(run_source("[1, 2] != [1, 2]"), Value::Bool(false)));
+
+        // Values are not cast before comparing, and values that can't be ordered compare as false
+        assert!(matches!(run_source("\"1\" == 1"), Value::Bool(false)));
+        assert!(matches!(run_source("\"1\" < 2"), Value::Bool(false)));
+    }
+
+    #[test]
+    fn evaluates_logical_operators() {
+        assert!(matches!(run_source("1 < 2 and 2 < 3"), Value::Bool(true)));
+        assert!(matches!(run_source("false or 0"), Value::Bool(false)));
+        assert!(matches!(run_source("true xor true"), Value::Bool(false)));
+        assert!(matches!(run_source("not \"\""), Value::Bool(true)));
+
+        // The right side is skipped when the left side decides the result
+        let source = "boom () {\n    boom()\n}\nfalse and boom() or true";
+        assert!(matches!(run_source(source), Value::Bool(true)));
+    }
+
+    #[test]
+    fn stores_bools_and_loops_on_comparisons() {
+        assert!(matches!(run_source("x = 5;\nflag: bool = x > 3;\nflag"), Value::Bool(true)));
+        assert!(matches!(run_source("flag: bool = 0;\nflag"), Value::Bool(false)));
+
+        let source = "i = 0;\nwhile i < 4 {\n    i = i + 1;\n}\ni";
+        assert!(matches!(run_source(source), Value::Int(4)));
+    }
+}
@@ -1,33 +1,37 @@
 use std::collections::HashMap;
-use crate::parser_core::value::Value;
+use crate::parser_core::value::{Type, Value};
+use std::cell::RefCell;
 use std::rc::{Rc};
 
 pub struct Environment {
     pub vars: HashMap<String, Value>,    // Var name to Value (from enum) to allow fast look up times
-    pub parent: Option<Rc<Environment>>, // Smart pointer back to parent environment to allow nested environments
+    pub types: HashMap<String, Type>,    // Declared type of every typed variable, later assignments are cast to it
+    pub parent: Option<Rc<RefCell<Environment>>>, // Smart pointer back to parent environment to allow nested environments
 }
 
 impl Environment {
-    pub fn new(parent: Option<Rc<Environment>>) -> Self {
+    pub fn new(parent: Option<Rc<RefCell<Environment>>>) -> Self {
         // Generate a new clean environment
         Environment {
             vars: HashMap::new(),
-            parent: parent,
+            types: HashMap::new(),
+            parent,
         }
     }
 
     pub fn search_for_var(&self, name: String) -> Value {
+        // If no value is found in any environment, return Null
+        self.lookup(&name).unwrap_or(Value::Undefined)
+    }
+
+    // Like search_for_var, but tells a missing variable apart from one holding undefined
+    pub fn lookup(&self, name: &str) -> Option<Value> {
         // First check if the value exists in the current environment
-        if let Some(val) = self.vars.get(&name) {
-            return val.clone();  // Return a clone of the value
+        if let Some(val) = self.vars.get(name) {
+            return Some(val.clone());  // Return a clone of the value
         }
-        
+
         // If not found, check parent environments
-        if let Some(parent_env) = &self.parent {
-            return parent_env.search_for_var(name);
-        }
-        
-        // If no value is found in any environment, return Null
-        Value::Undefined
+        self.parent.as_ref().and_then(|parent_env| parent_env.borrow().lookup(name))
     }
 }
\ No newline at end of file
@@ -0,0 +1,61 @@
+use std::fmt;
+
+use crate::diagnostics::diagnostic::Diagnostic;
+use crate::parser_core::span::Span;
+use crate::parser_core::tokenized::Verb;
+
+// An operation that failed while the program was running
+// Operations don't know where they were written, the interpreter attaches the span of the failing expression with at()
+#[derive(Clone, Debug)]
+pub enum RuntimeError {
+    DivisionByZero,
+    Overflow(Verb, String),               // The verb and the type whose range was left
+    TypeMismatch(String, Vec<String>),    // The operator or builtin and the types of the operands it isn't defined for
+    UndefinedVariable(String),
+    IndexOutOfRange(i64, usize),          // The index and the length of the list or str
+    InvalidCast(String, String),          // The type of the value and the type it couldn't be converted to
+}
+
+impl RuntimeError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeError::DivisionByZero => "E0411",
+            RuntimeError::Overflow(_, _) => "E0412",
+            RuntimeError::TypeMismatch(_, _) => "E0413",
+            RuntimeError::UndefinedVariable(_) => "E0414",
+            RuntimeError::IndexOutOfRange(_, _) => "E0415",
+            RuntimeError::InvalidCast(_, _) => "E0416",
+        }
+    }
+
+    // Failures that quietly produce undefined unless strict mode is on
+    pub fn is_lenient(&self) -> bool {
+        matches!(self, RuntimeError::TypeMismatch(_, _) | RuntimeError::UndefinedVariable(_) | RuntimeError::IndexOutOfRange(_, _))
+    }
+
+    pub fn at(self, span: &Span) -> Diagnostic {
+        let diagnostic = Diagnostic::error(self.code(), self.to_string(), Some(span.clone()));
+        match self {
+            RuntimeError::DivisionByZero => diagnostic.with_help("check the divisor before dividing, only float division produces infinity".to_string()),
+            RuntimeError::Overflow(_, type_name) if type_name == "int" => diagnostic.with_help("use a wider type such as `long` or `bigint`".to_string()),
+            RuntimeError::Overflow(_, type_name) if type_name == "long" => diagnostic.with_help("use `bigint` for integers of any size".to_string()),
+            _ => diagnostic,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeError::DivisionByZero => write!(f, "attempt to divide by zero"),
+            RuntimeError::Overflow(verb, type_name) => write!(f, "`{}` overflowed the range of `{}`", verb.symbol(), type_name),
+            RuntimeError::TypeMismatch(operation, operands) => {
+                let operands: Vec<String> = operands.iter().map(|name| format!("`{}`", name)).collect();
+                write!(f, "mismatched types: cannot apply `{}` to {}", operation, operands.join(" and "))
+            },
+            RuntimeError::UndefinedVariable(name) => write!(f, "cannot find `{}` in this scope", name),
+            RuntimeError::IndexOutOfRange(index, length) => write!(f, "index {} is out of range for a length of {}", index, length),
+            RuntimeError::InvalidCast(from, to) => write!(f, "cannot cast a `{}` value to `{}`", from, to),
+        }
+    }
+}
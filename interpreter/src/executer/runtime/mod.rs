@@ -1 +1,3 @@
-pub mod environment; 
\ No newline at end of file
+// Runtime values are parser_core::value::Value, literals and results share one type
+pub mod environment;
+pub mod error;
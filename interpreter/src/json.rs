@@ -0,0 +1,94 @@
+// Minimal JSON document model, enough to emit machine readable output without pulling in a serialization crate
+
+#[derive(Clone, Debug)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),   // Vec instead of a map so keys are written in a stable order
+}
+
+fn escape(text: &str, output: &mut String) {
+    output.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+impl Json {
+    pub fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(key, val)| (key.to_string(), val)).collect())
+    }
+
+    fn write(&self, output: &mut String) {
+        match self {
+            Json::Null => output.push_str("null"),
+            Json::Bool(b) => output.push_str(&b.to_string()),
+            Json::Int(n) => output.push_str(&n.to_string()),
+            // JSON has no representation for NaN or infinity
+            Json::Float(f) if !f.is_finite() => output.push_str("null"),
+            Json::Float(f) => output.push_str(&format!("{:?}", f)),
+            Json::Str(s) => escape(s, output),
+            Json::Array(items) => {
+                output.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        output.push(',');
+                    }
+                    item.write(output);
+                }
+                output.push(']');
+            },
+            Json::Object(fields) => {
+                output.push('{');
+                for (i, (key, val)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        output.push(',');
+                    }
+                    escape(key, output);
+                    output.push(':');
+                    val.write(output);
+                }
+                output.push('}');
+            },
+        }
+    }
+
+    // Compact single line encoding
+    pub fn encode(&self) -> String {
+        let mut output = String::new();
+        self.write(&mut output);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_nested_documents_in_key_order() {
+        let document = Json::object(vec![
+            ("b", Json::Array(vec![Json::Int(1), Json::Float(2.5), Json::Null])),
+            ("a", Json::Str("x".to_string())),
+        ]);
+        assert_eq!(document.encode(), r#"{"b":[1,2.5,null],"a":"x"}"#);
+    }
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(Json::Str("say \"hi\"\n\\\u{1}".to_string()).encode(), r#""say \"hi\"\n\\\u0001""#);
+    }
+}
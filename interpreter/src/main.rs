@@ -4,20 +4,87 @@ mod parser_core {
     pub mod tokenized;
     pub mod value;
     pub mod ast;
+    pub mod span;
+    pub mod desugar;
+    pub mod scanner;
+}
+
+mod diagnostics {
+    pub mod diagnostic;
+    pub mod render;
+    pub mod json;
+    pub mod emitter;
+}
+
+mod analyzer {
+    #[allow(clippy::module_inception)]
+    pub mod analyzer;
+}
+
+mod json;
+
+mod driver {
+    pub mod cli;
+    pub mod commands;
+    pub mod repl;
+    pub mod emit;
 }
 
 mod executer {
     pub mod runtime;
     pub mod interpreter;
+    pub mod builtins;
+    pub mod arithmetic;
 }
 
+use std::io::IsTerminal;
+
+use diagnostics::emitter::Emitter;
+use diagnostics::render::{Renderer, RenderMode};
+use driver::cli::{self, Command};
+use driver::commands;
+use executer::interpreter::STACK_SIZE;
+
 fn main() {
-    let mut lexer = parser_core::lexer::Lexer::new("C:\\Users\\austi\\projects\\Luma\\test.luma".to_string());
-    lexer.run();
+    // Luma calls recurse on the Rust stack, so the whole driver runs on a thread with room for the deepest allowed call chain
+    let code = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .and_then(|handle| handle.join().map_err(|_| std::io::Error::other("interpreter thread panicked")));
+
+    match code {
+        Ok(code) => std::process::exit(code),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(commands::FAILURE);
+        },
+    }
+}
+
+fn run() -> i32 {
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(val) => val,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, cli::USAGE);
+            std::process::exit(commands::USAGE);
+        },
+    };
 
-    let parser = parser_core::parser::Parser::new(lexer);
-    let ast = parser.run();
+    // Only color the output when a person is reading it
+    let mode = if std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none() {
+        RenderMode::Color
+    } else {
+        RenderMode::Plain
+    };
+    let mut emitter = Emitter::new(options.error_format, Renderer::new(mode));
 
-    let mut interpreter = executer::interpreter::Interpreter::new(ast);
-    println!("{:?}", interpreter.run());
+    match options.command {
+        Command::Run { file, args } => commands::run(&file, &args, options.strict, &mut emitter, &options.emit),
+        Command::Check { file } => commands::check(&file, &mut emitter, &options.emit),
+        Command::Repl => driver::repl::Repl::new(options.strict).run(&mut emitter),
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            commands::SUCCESS
+        },
+    }
 }
@@ -1,19 +1,123 @@
+#![allow(non_camel_case_types, clippy::upper_case_acronyms)]
+
+use std::collections::HashMap;
+
 use crate::parser_core::value;
 use crate::parser_core::tokenized;
+use crate::parser_core::span::Span;
 
 #[derive(Clone, Debug)]
 pub enum AST_type {
     Set,
     Return,
+    Function,   // `name: type (params) { ... }`, binds a Value::Function to the name
+    Mark,       // `name!`, a jump target, does nothing when run
+    Do(Lookup), // `name if cond?`, jumps to the marker when the condition (b) holds
+    Unset,      // Removes a variable from the environment, only generated (for the hidden variables of a `for` loop)
+}
+
+// Which marker with a given name a Do statement jumps to
+#[derive(Clone, Debug, PartialEq)]
+pub enum Lookup {
+    Forward,    // `name`, the first marker after the jump
+    Back,       // `~name`, the closest marker before the jump
+    Anywhere,   // `*name`, the first marker in the block
+}
+
+impl Lookup {
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Lookup::Forward => "",
+            Lookup::Back => "~",
+            Lookup::Anywhere => "*",
+        }
+    }
+}
+
+// Expression tree built by the parser, operator precedence is encoded in the shape of the tree
+#[derive(Clone, Debug)]
+pub enum Expression {
+    Noun(value::Value, Span),
+    Unary(tokenized::Verb, Box<Expression>, Span),
+    Binary(tokenized::Verb, Box<Expression>, Box<Expression>, Span),
+    Call(String, Vec<Expression>, Span),   // `name(a, b)`, arguments are evaluated left to right
+    List(Vec<Expression>, Span),           // `[a, b]`
+    Index(Box<Expression>, Box<Expression>, Span),   // `list[i]`, also indexes the chars of a str
+    Cast(Box<Expression>, value::Type, Span),        // `x:int`, a value that can't be converted is an error
+    Concat(Vec<Expression>, Span),         // `"a {b}"`, every part is rendered as a str and joined in order
+}
+
+impl Expression {
+    pub fn span(&self) -> &Span {
+        match self {
+            Expression::Noun(_, span) => span,
+            Expression::Unary(_, _, span) => span,
+            Expression::Binary(_, _, _, span) => span,
+            Expression::Call(_, _, span) => span,
+            Expression::List(_, span) => span,
+            Expression::Index(_, _, span) => span,
+            Expression::Cast(_, _, span) => span,
+            Expression::Concat(_, span) => span,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct AST_statement {
     pub statement_type: AST_type,
     pub a: value::Value,
-    pub b: Vec<tokenized::Token>,
+    pub declared_type: Option<value::Type>,   // Set by `name: type = expr;`, values assigned to the name are cast to it
+    pub b: Expression,
+    pub span: Span,
+    pub doc: Option<String>,   // `///` comments written before a declaration (a set statement or a function), kept for tooling
 }
 
+#[derive(Clone, Debug)]
 pub struct AST {
     pub statements: Vec<AST_statement>,
-}
\ No newline at end of file
+    pub markers: HashMap<String, Vec<usize>>,   // Marker name to the index of every Mark statement with that name, ascending
+    pub strict: bool,   // Set on a file's top level block by the `#strict` pragma
+}
+
+impl AST {
+    pub fn new(statements: Vec<AST_statement>) -> Self {
+        let mut markers: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, statement) in statements.iter().enumerate() {
+            if let (AST_type::Mark, value::Value::VarName(name)) = (&statement.statement_type, &statement.a) {
+                markers.entry(name.clone()).or_default().push(index);
+            }
+        }
+
+        AST {
+            statements,
+            markers,
+            strict: false,
+        }
+    }
+
+    // Index of the marker a jump from statement `from` lands on, None when there is no such marker
+    pub fn jump_target(&self, name: &str, lookup: &Lookup, from: usize) -> Option<usize> {
+        let indices = self.markers.get(name)?;
+        match lookup {
+            Lookup::Forward => indices.get(indices.partition_point(|&index| index <= from)).copied(),
+            Lookup::Back => indices.partition_point(|&index| index < from).checked_sub(1).map(|position| indices[position]),
+            Lookup::Anywhere => indices.first().copied(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    pub name: String,
+    pub param_type: Option<value::Type>,   // Arguments are cast to it, untyped parameters accept any value
+}
+
+// Body of a function declaration, shared between the AST and every Value::Function bound to it
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<Parameter>,
+    pub return_type: Option<value::Type>,
+    pub body: AST,
+    pub span: Span,   // The header line, `name: type (params) {`
+}
@@ -0,0 +1,157 @@
+use crate::parser_core::ast::{AST_statement, AST_type, Expression, Lookup};
+use crate::parser_core::span::Span;
+use crate::parser_core::tokenized::Verb;
+use crate::parser_core::value::Value;
+
+// **GOAL:** Rewrite structured control flow into the Mark/Do jump model, so the interpreter only ever steps through flat blocks
+// Generated markers and variables start with `#`, which source can't spell, so they never clash with names in the program
+
+// One `if` / `else if` / `else` arm, the condition is None for the final `else`
+pub struct Branch {
+    pub condition: Option<Expression>,
+    pub body: Vec<AST_statement>,
+    pub span: Span,   // The arm's header line
+}
+
+fn mark(name: &str, span: &Span) -> AST_statement {
+    AST_statement {
+        statement_type: AST_type::Mark,
+        a: Value::VarName(name.to_string()),
+        declared_type: None,
+        b: Expression::Noun(Value::Undefined, span.clone()),
+        span: span.clone(),
+        doc: None,
+    }
+}
+
+// A jump with no condition is always taken, like `name?` in source
+fn jump(name: &str, lookup: Lookup, condition: Option<Expression>, span: &Span) -> AST_statement {
+    AST_statement {
+        statement_type: AST_type::Do(lookup),
+        a: Value::VarName(name.to_string()),
+        declared_type: None,
+        b: condition.unwrap_or_else(|| Expression::Noun(Value::Bool(true), span.clone())),
+        span: span.clone(),
+        doc: None,
+    }
+}
+
+fn set(name: &str, expression: Expression, span: &Span) -> AST_statement {
+    AST_statement {
+        statement_type: AST_type::Set,
+        a: Value::VarName(name.to_string()),
+        declared_type: None,
+        b: expression,
+        span: span.clone(),
+        doc: None,
+    }
+}
+
+fn unset(name: &str, span: &Span) -> AST_statement {
+    AST_statement {
+        statement_type: AST_type::Unset,
+        a: Value::VarName(name.to_string()),
+        declared_type: None,
+        b: Expression::Noun(Value::Undefined, span.clone()),
+        span: span.clone(),
+        doc: None,
+    }
+}
+
+fn var(name: &str, span: &Span) -> Expression {
+    Expression::Noun(Value::VarName(name.to_string()), span.clone())
+}
+
+// if a { A } else if b { B } else { C }
+//
+//     #if0.next0 if not a?    #if0.next1 if not b?
+//     A                       B
+//     #if0.end?               #if0.end?
+//     #if0.next0!             #if0.next1!
+//                                                  C
+//                                                  #if0.end!
+pub fn lower_if(label: &str, branches: Vec<Branch>, end_span: &Span) -> Vec<AST_statement> {
+    let end = format!("{}.end", label);
+    let mut statements = Vec::new();
+
+    for (index, branch) in branches.into_iter().enumerate() {
+        match branch.condition {
+            Some(condition) => {
+                let next = format!("{}.next{}", label, index);
+                let skip = Expression::Unary(Verb::Not, Box::new(condition), branch.span.clone());
+
+                statements.push(jump(&next, Lookup::Forward, Some(skip), &branch.span));
+                statements.extend(branch.body);
+                statements.push(jump(&end, Lookup::Forward, None, end_span));
+                statements.push(mark(&next, end_span));
+            },
+            None => statements.extend(branch.body),
+        }
+    }
+
+    statements.push(mark(&end, end_span));
+    statements
+}
+
+// while a { A }
+//
+//     #while0.top!
+//     #while0.end if not a?
+//     A
+//     ~#while0.top?
+//     #while0.end!
+pub fn lower_while(label: &str, condition: Expression, body: Vec<AST_statement>, span: &Span, end_span: &Span) -> Vec<AST_statement> {
+    let top = format!("{}.top", label);
+    let end = format!("{}.end", label);
+    let exit = Expression::Unary(Verb::Not, Box::new(condition), span.clone());
+
+    let mut statements = vec![
+        mark(&top, span),
+        jump(&end, Lookup::Forward, Some(exit), span),
+    ];
+    statements.extend(body);
+    statements.push(jump(&top, Lookup::Back, None, end_span));
+    statements.push(mark(&end, end_span));
+    statements
+}
+
+// for x in list { A }, the list is evaluated once before the first iteration
+//
+//     #for0.list = list;
+//     #for0.index = 0;
+//     while #for0.index < #len(#for0.list) {
+//         x = #for0.list[#for0.index];
+//         A
+//         #for0.index = #for0.index + 1;
+//     }
+//     unset #for0.list
+//     unset #for0.index
+pub fn lower_for(label: &str, name: &str, iterable: Expression, body: Vec<AST_statement>, span: &Span, end_span: &Span) -> Vec<AST_statement> {
+    let list = format!("{}.list", label);
+    let index = format!("{}.index", label);
+
+    // The builtin is called as `#len` so a program defining its own `len` doesn't change how loops run
+    let remaining = Expression::Binary(
+        Verb::Less,
+        Box::new(var(&index, span)),
+        Box::new(Expression::Call("#len".to_string(), vec![var(&list, span)], span.clone())),
+        span.clone(),
+    );
+    let item = Expression::Index(Box::new(var(&list, span)), Box::new(var(&index, span)), span.clone());
+    let step = Expression::Binary(Verb::Add, Box::new(var(&index, end_span)), Box::new(Expression::Noun(Value::Int(1), end_span.clone())), end_span.clone());
+
+    let mut loop_body = vec![set(name, item, span)];
+    loop_body.extend(body);
+    loop_body.push(set(&index, step, end_span));
+
+    let mut statements = vec![
+        set(&list, iterable, span),
+        set(&index, Expression::Noun(Value::Int(0), span.clone()), span),
+    ];
+    statements.extend(lower_while(label, remaining, loop_body, span, end_span));
+
+    // The hidden variables are dropped once the loop exits, so they don't pile up in the environment
+    statements.push(unset(&list, end_span));
+    statements.push(unset(&index, end_span));
+    statements
+}
@@ -1,13 +1,35 @@
-use std::io::{self, Error, Read};
-use std::fs::{self, File}; 
-use std::path::{Path, PathBuf};
-
-use crate::parser_core::value;
-use crate::parser_core::value::CastTo;
-use crate::parser_core::tokenized;
-use std::collections::HashMap;
-
-// **GOAL:** Read file contents, split the file contents into a Vec of lines, for each line split the line by its parts, and insert types where necessary
+use std::io::{self, Read};
+use std::fs::File;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::parser_core::tokenized::{self, Keyword, Suffix, Token, TokenList, Verb};
+use crate::parser_core::scanner::{Lexeme, Scanner};
+use crate::parser_core::span::Span;
+use crate::diagnostics::diagnostic::Diagnostic;
+
+// **GOAL:** Read file contents, scan them into tokens and group the tokens into statements
+
+// Whether a statement without a suffix carries on past a line break instead of ending there as a return statement
+// It does while a `(` or `[` is open, after a line ending in a verb or a keyword (`x +`, `for x in`),
+// and before a line starting with a verb that can't begin an expression (`+ y`, `== y`) or with a suffix
+fn continues(last: &Token, depth: usize, next: Option<&Lexeme>) -> bool {
+    let trailing = match last {
+        Token::Verb(Verb::CloseParen | Verb::CloseBracket, _) => false,
+        Token::Verb(_, _) | Token::Keyword(_, _) => true,
+        _ => false,
+    };
+
+    // `-`, `not`, `(` and `[` start expressions and `~` and `*` start jumps, so a line starting with one of them is a new statement
+    let leading = match next {
+        Some(Lexeme::Token(Token::Verb(verb, _))) => !matches!(verb, Verb::Sub | Verb::Not | Verb::OpenParen | Verb::OpenBracket | Verb::Tilde | Verb::Mult),
+        Some(Lexeme::Token(Token::Keyword(Keyword::In, _))) => true,
+        Some(Lexeme::Suffix(suffix, _)) => !matches!(suffix, Suffix::Close),
+        _ => false,
+    };
+
+    depth > 0 || trailing || leading
+}
 
 fn read_file(file_path: &str) -> Result<String, io::Error> {
     let path = Path::new(file_path);
@@ -20,101 +42,148 @@ fn read_file(file_path: &str) -> Result<String, io::Error> {
 }
 
 pub struct Lexer {
+    pub file_name: Rc<str>,
     pub file_contents: String,
     pub tokenized_lines: tokenized::Tokenized,
+    pub strict: bool,   // Set by a `#strict` pragma
 }
 
 impl Lexer {
-    pub fn new(file_path: String) -> Self {
+    pub fn new(file_path: String) -> Result<Self, Diagnostic> {
+        match read_file(file_path.as_str()) {
+            Ok(val) => Ok(Self::from_source(file_path, val)),
+            Err(err) => Err(Diagnostic::error("E0001", format!("could not read `{}`: {}", file_path, err), None)),
+        }
+    }
+
+    pub fn from_source(file_name: String, file_contents: String) -> Self {
         Lexer {
-            file_contents: match read_file(file_path.as_str()) {
-                Ok(val) => val,
-                Err(err) => panic!("{}", err),
-            },
+            file_name: Rc::from(file_name),
+            file_contents,
             tokenized_lines: tokenized::Tokenized {
                 lines: Vec::new(),
             },
+            strict: false,
         }
     }
 
-    pub fn run(&mut self) {
-        // **GOAL:** Loop through every line and convert to a TokenList
-        let mut split_line = self.file_contents.split("\n");
-
-        let mut next_line = split_line.next();
-
-        while let Some(mut line) = next_line {
-            line = match line.split("//").next() {
-                Some(val) => val,
-                None => panic!("Failed to isolate non comment section of line")
-            };
-
-            line = line.trim();
-            
-            if line == "" {
-                next_line = split_line.next();
-                continue;
-            }
-
-            // Manages all of the special characters in the language
-            let actions = HashMap::from([
-                ('+', tokenized::Verb::Add),
-                ('-', tokenized::Verb::Sub),
-                ('*', tokenized::Verb::Mult),
-                ('/', tokenized::Verb::Div),
-                ('=', tokenized::Verb::Set),
-            ]);
-
-            // Check for suffix in the last character
-            let mut chars: Vec<char> = line.chars().collect();
-            let suffix = if let Some(&last_char) = chars.last() {
-                match last_char {
-                    ';' => Some(tokenized::Suffix::Set),
-                    _ => {
-                        chars.push(last_char);  // Cancel out suffix removal to keep the suffix
-                        Some(tokenized::Suffix::Return)
-                    },
-                }
-            } else {
-                None
-            };
-
-            // Remove the last character if it was a suffix
-            if suffix.is_some() {
-                chars.pop();
-            }
-
-            // Sliding Window approach: loop through each character in the line and reference it with the actions list, O(n) time complexity
-            let mut slider = String::new();
-            let mut token_list: Vec<tokenized::Token> = Vec::new();
+    fn push_statement(&mut self, objects: Vec<Token>, suffix: Suffix, span: Span, docs: &mut Vec<String>) {
+        self.tokenized_lines.lines.push(TokenList {
+            objects,
+            suffix: Some(suffix),
+            span,
+            doc: (!docs.is_empty()).then(|| std::mem::take(docs).join("\n")),
+        });
+    }
 
-            for character in chars {
-                if let Some(action) = actions.get(&character) {
-                    if !slider.trim().is_empty() {
-                        token_list.push(tokenized::Token::Noun(value::Value::evaluate(slider.trim().to_string())));
+    pub fn run(&mut self) -> Result<(), Vec<Diagnostic>> {
+        // **GOAL:** Scan the whole file, then cut the tokens into statements at every suffix
+        // A statement without a suffix is a return statement, it ends at a line break where the expression is complete
+        let (lexemes, mut diagnostics) = Scanner::new(self.file_name.clone(), &self.file_contents).run();
+
+        let mut objects: Vec<Token> = Vec::new();
+        let mut span: Option<Span> = None;
+        let mut depth: usize = 0;   // `(` and `[` left open by the statement so far
+        let mut docs: Vec<String> = Vec::new();
+
+        for (index, lexeme) in lexemes.iter().enumerate() {
+            match lexeme {
+                // Pragmas (`#strict`) configure the whole file, so they have to come before its first statement
+                Lexeme::Pragma(name, pragma_span) if objects.is_empty() && self.tokenized_lines.lines.is_empty() => match name.as_str() {
+                    "strict" => self.strict = true,
+                    _ => diagnostics.push(Diagnostic::error("E0102", format!("unknown pragma `#{}`", name), Some(pragma_span.clone()))
+                        .with_help("the available pragmas are #strict".to_string())),
+                },
+                Lexeme::Pragma(name, pragma_span) => diagnostics.push(Diagnostic::error("E0103", format!("pragma `#{}` after the first statement", name), Some(pragma_span.clone()))
+                    .with_help("move it to the top of the file".to_string())),
+                Lexeme::Doc(text) => docs.push(text.clone()),
+                Lexeme::Token(token) => {
+                    match token {
+                        Token::Verb(Verb::OpenParen | Verb::OpenBracket, _) => depth += 1,
+                        Token::Verb(Verb::CloseParen | Verb::CloseBracket, _) => depth = depth.saturating_sub(1),
+                        _ => {},
                     }
+                    span = Some(span.map_or(token.span().clone(), |span| span.to(token.span())));
+                    objects.push(token.clone());
+                },
+                // A `}` closes the block on its own, whatever came before it on the line is a return statement
+                // Doc comments left at the end of the block belong to nothing, they don't carry over to what follows it
+                Lexeme::Suffix(Suffix::Close, close_span) => {
+                    if let Some(span) = span.take() {
+                        self.push_statement(std::mem::take(&mut objects), Suffix::Return, span, &mut docs);
+                    }
+                    docs.clear();
+                    self.tokenized_lines.lines.push(TokenList { objects: Vec::new(), suffix: Some(Suffix::Close), span: close_span.clone(), doc: None });
+                    depth = 0;
+                },
+                Lexeme::Suffix(suffix, suffix_span) => {
+                    let span = span.take().map_or(suffix_span.clone(), |span| span.to(suffix_span));
+                    self.push_statement(std::mem::take(&mut objects), suffix.clone(), span, &mut docs);
+                    depth = 0;
+                },
+                Lexeme::Newline => {
+                    let next = lexemes[index + 1..].iter().find(|lexeme| !matches!(lexeme, Lexeme::Newline | Lexeme::Doc(_)));
+                    if objects.last().is_some_and(|last| !continues(last, depth, next)) {
+                        if let Some(span) = span.take() {
+                            self.push_statement(std::mem::take(&mut objects), Suffix::Return, span, &mut docs);
+                        }
+                        depth = 0;
+                    }
+                },
+            }
+        }
 
-                    token_list.push(tokenized::Token::Verb(action.clone()));
+        if let Some(span) = span {
+            self.push_statement(objects, Suffix::Return, span, &mut docs);
+        }
 
-                    slider = String::new();
-                    continue;
-                }
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(diagnostics)
+        }
+    }
+}
 
-                slider.push(character);
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The suffix name and first line of every statement
+    fn statements(source: &str) -> Vec<(&'static str, usize)> {
+        let mut lexer = Lexer::from_source("<test>".to_string(), source.to_string());
+        lexer.run().unwrap();
+        lexer.tokenized_lines.lines.iter()
+            .map(|token_list| (token_list.suffix.as_ref().unwrap().name(), token_list.span.line))
+            .collect()
+    }
 
-            // Handle the last token if there is one
-            if !slider.trim().is_empty() {
-                token_list.push(tokenized::Token::Noun(value::Value::evaluate(slider.trim().to_string())));
-            }
+    #[test]
+    fn suffixes_end_statements_anywhere_on_a_line() {
+        assert_eq!(statements("x = 1; y = 2;\ntop! x"), [("set", 1), ("set", 1), ("mark", 2), ("return", 2)]);
+        assert_eq!(statements("f () { 1 }\n} else {"), [("open", 1), ("return", 1), ("close", 1), ("close", 2), ("open", 2)]);
+    }
 
-            // Add the token list to our lines
-            self.tokenized_lines.lines.push(tokenized::TokenList {
-                objects: token_list,
-                suffix: suffix,
-            });
+    #[test]
+    fn statements_continue_over_line_breaks() {
+        // An open `(` or `[`, a trailing verb or keyword, or a leading verb that can't start an expression
+        assert_eq!(statements("x = f(\n    1,\n    2\n);"), [("set", 1)]);
+        assert_eq!(statements("x = 1 +\n    2;"), [("set", 1)]);
+        assert_eq!(statements("x = 1\n    + 2\n    == 3;"), [("set", 1)]);
+        assert_eq!(statements("for x\n    in xs\n{"), [("open", 1)]);
+        assert_eq!(statements("done if x\n    > 5?"), [("do", 1)]);
+
+        // A complete expression ends at the line break, a leading `-` or `*` starts a new statement
+        assert_eq!(statements("x\n-1"), [("return", 1), ("return", 2)]);
+        assert_eq!(statements("x\n*top?"), [("return", 1), ("do", 2)]);
+        assert_eq!(statements("x\n\n/// doc\n~top?"), [("return", 1), ("do", 4)]);
+    }
 
-            next_line = split_line.next();
-        }
+    #[test]
+    fn doc_comments_do_not_leave_their_block() {
+        let mut lexer = Lexer::from_source("<test>".to_string(), "f () {\n    1\n    /// stray\n}\n/// Adds one.\ng () { 2 }".to_string());
+        lexer.run().unwrap();
+        let docs: Vec<Option<&str>> = lexer.tokenized_lines.lines.iter().map(|token_list| token_list.doc.as_deref()).collect();
+        assert_eq!(docs, [None, None, None, Some("Adds one."), None, None]);
     }
-}
\ No newline at end of file
+}
@@ -1,90 +1,846 @@
 use crate::parser_core::lexer::Lexer;
-use crate::parser_core::ast::{AST, AST_statement, AST_type};
-use crate::parser_core::tokenized::{Token, Verb};
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::parser_core::ast::{AST, AST_statement, AST_type, Expression, Function, Lookup, Parameter};
+use crate::parser_core::tokenized::{Keyword, Segment, Token, TokenList, Verb};
 use crate::parser_core::value;
+use crate::parser_core::desugar::{self, Branch};
 use crate::parser_core::tokenized;
-use crate::parser_core;
+use crate::parser_core::span::Span;
+use crate::diagnostics::diagnostic::Diagnostic;
+
+// Binding power of each binary verb, higher binds tighter (all binary verbs are left associative)
+fn precedence(verb: &Verb) -> Option<u8> {
+    match verb {
+        Verb::Or => Some(1),
+        Verb::Xor => Some(2),
+        Verb::And => Some(3),
+        Verb::Eq | Verb::NotEq | Verb::Less | Verb::LessEq | Verb::Greater | Verb::GreaterEq => Some(5),
+        Verb::Add | Verb::Sub => Some(6),
+        Verb::Mult | Verb::Div => Some(7),
+        _ => None,
+    }
+}
+
+// Prefix `not` sits between the logical verbs and the comparisons, so `not a == b` is `not (a == b)` and `not a and b` is `(not a) and b`
+const NOT_PRECEDENCE: u8 = 4;
+
+// Precedence climbing over a flat token slice: [Noun, Verb, Noun, Verb, Noun] -> Expression tree
+struct ExpressionParser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    statement_span: &'a Span,   // Reported when the expression ends early
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn new(tokens: &'a [Token], statement_span: &'a Span) -> Self {
+        ExpressionParser {
+            tokens,
+            position: 0,
+            statement_span,
+        }
+    }
+
+    fn parse(mut self) -> Result<Expression, Diagnostic> {
+        let expression = self.parse_binary(1)?;
+
+        if let Some(token) = self.tokens.get(self.position) {
+            return Err(Diagnostic::error("E0202", "unexpected token after the end of the expression".to_string(), Some(token.span().clone())));
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expression, Diagnostic> {
+        let mut left = self.parse_operand()?;
+
+        while let Some(Token::Verb(verb, _)) = self.tokens.get(self.position) {
+            let verb_precedence = match precedence(verb) {
+                Some(val) if val >= min_precedence => val,
+                _ => break,
+            };
+            let verb = verb.clone();
+            self.position += 1;
+
+            // Left associativity: the right hand side may only absorb verbs that bind strictly tighter
+            let right = self.parse_binary(verb_precedence + 1)?;
+            let span = left.span().to(right.span());
+            left = Expression::Binary(verb, Box::new(left), Box::new(right), span);
+        }
+
+        Ok(left)
+    }
+
+    // A primary value followed by any number of `[index]` suffixes
+    fn parse_operand(&mut self) -> Result<Expression, Diagnostic> {
+        let mut operand = self.parse_primary()?;
+
+        loop {
+            match self.tokens.get(self.position) {
+                Some(Token::Verb(Verb::OpenBracket, open_span)) => {
+                    self.position += 1;
+                    let index = self.parse_binary(1)?;
+
+                    match self.tokens.get(self.position) {
+                        Some(Token::Verb(Verb::CloseBracket, close_span)) => {
+                            self.position += 1;
+                            let span = operand.span().to(close_span);
+                            operand = Expression::Index(Box::new(operand), Box::new(index), span);
+                        },
+                        _ => return Err(Diagnostic::error("E0203", "unclosed '['".to_string(), Some(open_span.clone()))
+                            .with_help("add a ']' to close the index".to_string())),
+                    }
+                },
+                // `x:int`, binds as tightly as indexing so `x:int < 120:int` compares two casts
+                Some(Token::Verb(Verb::Colon, colon_span)) => {
+                    let target = match self.tokens.get(self.position + 1) {
+                        Some(Token::Noun(value::Value::VarName(type_name), type_span)) => (parse_type(type_name, type_span)?, type_span),
+                        _ => return Err(Diagnostic::error("E0206", "expected a type after ':'".to_string(), Some(colon_span.clone()))
+                            .with_help("write a cast as `value:type`, e.g. `x:int`".to_string())),
+                    };
+
+                    self.position += 2;
+                    let span = operand.span().to(target.1);
+                    operand = Expression::Cast(Box::new(operand), target.0, span);
+                },
+                _ => return Ok(operand),
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, Diagnostic> {
+        match self.tokens.get(self.position) {
+            Some(Token::Noun(value::Value::VarName(name), span)) if matches!(self.tokens.get(self.position + 1), Some(Token::Verb(Verb::OpenParen, _))) => {
+                self.position += 1;
+                let (args, close_span) = self.parse_items(Verb::CloseParen, "separate arguments with ',' and add a ')' to close the call")?;
+                Ok(Expression::Call(name.clone(), args, span.to(&close_span)))
+            },
+            Some(Token::Noun(val, span)) => {
+                self.position += 1;
+                Ok(Expression::Noun(val.clone(), span.clone()))
+            },
+            Some(Token::Interpolated(segments, span)) => {
+                self.position += 1;
+                let mut parts = Vec::new();
+                for segment in segments {
+                    parts.push(match segment {
+                        Segment::Text(text, text_span) => Expression::Noun(value::Value::Str(text.clone()), text_span.clone()),
+                        Segment::Expression(tokens, segment_span) => ExpressionParser::new(tokens, segment_span).parse()?,
+                    });
+                }
+                Ok(Expression::Concat(parts, span.clone()))
+            },
+            Some(Token::Verb(Verb::Sub, span)) => {
+                // Prefix negation binds tighter than every binary verb, so -a * b is (-a) * b
+                self.position += 1;
+                let operand = self.parse_operand()?;
+                let span = span.to(operand.span());
+                Ok(Expression::Unary(Verb::Sub, Box::new(operand), span))
+            },
+            Some(Token::Verb(Verb::Not, span)) => {
+                self.position += 1;
+                let operand = self.parse_binary(NOT_PRECEDENCE)?;
+                let span = span.to(operand.span());
+                Ok(Expression::Unary(Verb::Not, Box::new(operand), span))
+            },
+            Some(Token::Verb(Verb::OpenParen, open_span)) => {
+                self.position += 1;
+                let expression = self.parse_binary(1)?;
+
+                match self.tokens.get(self.position) {
+                    Some(Token::Verb(Verb::CloseParen, _)) => self.position += 1,
+                    _ => return Err(Diagnostic::error("E0203", "unclosed '('".to_string(), Some(open_span.clone()))
+                        .with_help("add a ')' to close the group".to_string())),
+                }
+
+                Ok(expression)
+            },
+            Some(Token::Verb(Verb::OpenBracket, open_span)) => {
+                let (items, close_span) = self.parse_items(Verb::CloseBracket, "separate items with ',' and add a ']' to close the list")?;
+                Ok(Expression::List(items, open_span.to(&close_span)))
+            },
+            Some(Token::Verb(verb, span)) => Err(Diagnostic::error("E0201", format!("expected a value, found '{}'", verb.symbol()), Some(span.clone()))),
+            Some(Token::Keyword(keyword, span)) => Err(Diagnostic::error("E0201", format!("expected a value, found the keyword `{}`", keyword.name()), Some(span.clone()))),
+            None => Err(Diagnostic::error("E0201", "expected a value, found the end of the statement".to_string(), Some(self.statement_span.clone()))),
+        }
+    }
+
+    // Comma separated expressions up to the closing verb, for call arguments and list items
+    // The position is on the opening '(' or '[', returns the items and the span of the closing verb
+    fn parse_items(&mut self, close: Verb, help: &str) -> Result<(Vec<Expression>, Span), Diagnostic> {
+        let open_span = self.tokens[self.position].span().clone();
+        let open_symbol = match &self.tokens[self.position] {
+            Token::Verb(verb, _) => verb.symbol(),
+            _ => "",
+        };
+        self.position += 1;
+
+        let is_close = |token: Option<&Token>| matches!(token, Some(Token::Verb(verb, _)) if verb.symbol() == close.symbol());
+        let unclosed = || Diagnostic::error("E0203", format!("unclosed '{}'", open_symbol), Some(open_span.clone()))
+            .with_help(help.to_string());
+
+        let mut items = Vec::new();
+        if !is_close(self.tokens.get(self.position)) {
+            loop {
+                items.push(self.parse_binary(1)?);
+
+                match self.tokens.get(self.position) {
+                    Some(Token::Verb(Verb::Comma, _)) => self.position += 1,
+                    token if is_close(token) => break,
+                    _ => return Err(unclosed()),
+                }
+            }
+        }
+
+        let close_span = self.tokens[self.position].span().clone();
+        self.position += 1;
+        Ok((items, close_span))
+    }
+}
+
+fn parse_type(type_name: &str, type_span: &Span) -> Result<value::Type, Diagnostic> {
+    value::Type::from_name(type_name).ok_or_else(|| Diagnostic::error("E0206", format!("unknown type `{}`", type_name), Some(type_span.clone()))
+        .with_help("the available types are int, long, bigint, float, str, char and bool".to_string()))
+}
 
 pub struct Parser {
     lexer: Lexer,
+    labels: Cell<usize>,   // Count of structured statements lowered so far, numbers their generated markers
 }
 
 impl Parser {
     pub fn new(lexer: Lexer) -> Self {
         Parser {
-            lexer: lexer,
+            lexer,
+            labels: Cell::new(0),
         }
     }
 
-    pub fn run(&self) -> AST {
+    // Unique prefix for the markers and variables generated for one structured statement (`#while3`)
+    fn label(&self, kind: &str) -> String {
+        let count = self.labels.get();
+        self.labels.set(count + 1);
+        format!("#{}{}", kind, count)
+    }
+
+    pub fn tokens(&self) -> &tokenized::Tokenized {
+        &self.lexer.tokenized_lines
+    }
+
+    fn parse_statement(token_list: &TokenList) -> Result<AST_statement, Diagnostic> {
+        let suffix = match &token_list.suffix {
+            Some(val) => val,
+            None => return Err(Diagnostic::error("E0205", "statement has no suffix".to_string(), Some(token_list.span.clone()))),
+        };
+
+        match suffix {
+            tokenized::Suffix::Set => {
+                // **GOAL** We need to identify the variable name, and from there evaluate expression
+                // Load variable name from the left of the = and build an expression tree from the right
+                let set_index = match token_list.objects.iter().position(|token| matches!(token, Token::Verb(Verb::Set, _))) {
+                    Some(val) => val,
+                    None => return Err(Diagnostic::error("E0204", "expected '=' in a statement ending with ';'".to_string(), Some(token_list.span.clone()))
+                        .with_help("set statements have the form `name = value;`, remove the ';' to return the value instead".to_string())),
+                };
+
+                let (a, declared_type) = match &token_list.objects[..set_index] {
+                    [Token::Noun(val @ value::Value::VarName(_), _)] => (val.clone(), None),
+                    [Token::Noun(val @ value::Value::VarName(_), _), Token::Verb(Verb::Colon, _), Token::Noun(value::Value::VarName(type_name), type_span)] => {
+                        (val.clone(), Some(parse_type(type_name, type_span)?))
+                    },
+                    _ => return Err(Diagnostic::error("E0204", "expected a variable name, optionally followed by `: type`, on the left of '='".to_string(), Some(token_list.span.clone()))
+                        .with_help("set statements have the form `name = value;` or `name: type = value;`".to_string())),
+                };
+
+                Ok(AST_statement {
+                    statement_type: AST_type::Set,
+                    a,
+                    declared_type,
+                    b: ExpressionParser::new(&token_list.objects[set_index + 1..], &token_list.span).parse()?,
+                    span: token_list.span.clone(),
+                    doc: token_list.doc.clone(),
+                })
+            },
+            tokenized::Suffix::Return => {
+                // `x = 1` without its `;` runs until the end of the line and is read as a return statement
+                let b = ExpressionParser::new(&token_list.objects, &token_list.span).parse().map_err(|err| {
+                    let assigns = token_list.objects.iter().any(|token| matches!(token, Token::Verb(Verb::Set, _)));
+                    if assigns && err.code == "E0202" {
+                        err.with_help("end a set statement with ';'".to_string())
+                    } else {
+                        err
+                    }
+                })?;
+
+                Ok(AST_statement {
+                    statement_type: AST_type::Return,
+                    a: value::Value::Undefined,
+                    declared_type: None,
+                    b,
+                    span: token_list.span.clone(),
+                    doc: None,
+                })
+            },
+            tokenized::Suffix::Mark => match token_list.objects.as_slice() {
+                [Token::Noun(val @ value::Value::VarName(_), _)] => Ok(AST_statement {
+                    statement_type: AST_type::Mark,
+                    a: val.clone(),
+                    declared_type: None,
+                    b: Expression::Noun(value::Value::Undefined, token_list.span.clone()),
+                    span: token_list.span.clone(),
+                    doc: None,
+                }),
+                _ => Err(Diagnostic::error("E0211", "expected a marker name before '!'".to_string(), Some(token_list.span.clone()))
+                    .with_help("markers have the form `name!`".to_string())),
+            },
+            tokenized::Suffix::Do => {
+                let malformed = |span: &Span, message: &str| Diagnostic::error("E0211", message.to_string(), Some(span.clone()))
+                    .with_help("jumps have the form `name?` or `name if condition?`, prefix the name with `~` or `*` to search backwards or the whole block".to_string());
+
+                let (lookup, rest) = match token_list.objects.as_slice() {
+                    [Token::Verb(Verb::Tilde, _), rest @ ..] => (Lookup::Back, rest),
+                    [Token::Verb(Verb::Mult, _), rest @ ..] => (Lookup::Anywhere, rest),
+                    rest => (Lookup::Forward, rest),
+                };
+
+                let (a, name_span, rest) = match rest {
+                    [Token::Noun(val @ value::Value::VarName(_), name_span), rest @ ..] => (val.clone(), name_span, rest),
+                    _ => return Err(malformed(&token_list.span, "expected a marker name before '?'")),
+                };
+
+                let b = match rest {
+                    // A jump without a condition is always taken
+                    [] => Expression::Noun(value::Value::Bool(true), name_span.clone()),
+                    [Token::Keyword(Keyword::If, _), condition @ ..] => ExpressionParser::new(condition, &token_list.span).parse()?,
+                    [token, ..] => return Err(malformed(token.span(), "expected `if` after the marker name")),
+                };
+
+                Ok(AST_statement {
+                    statement_type: AST_type::Do(lookup),
+                    a,
+                    declared_type: None,
+                    b,
+                    span: token_list.span.clone(),
+                    doc: None,
+                })
+            },
+            tokenized::Suffix::Open | tokenized::Suffix::Close => unreachable!("blocks are handled by parse_block"),
+        }
+    }
+
+    // Header of a function declaration: `name: type (a: type, b) {`, the return type and parameter types are optional
+    fn parse_function_header(token_list: &TokenList) -> Result<(String, Vec<Parameter>, Option<value::Type>), Diagnostic> {
+        let malformed = |span: &Span| Diagnostic::error("E0207", "malformed function declaration".to_string(), Some(span.clone()))
+            .with_help("functions are declared as `name: type (param: type, ...) {`".to_string());
+
+        let tokens = &token_list.objects;
+        let (name, mut position) = match tokens.first() {
+            Some(Token::Noun(value::Value::VarName(name), _)) => (name.clone(), 1),
+            _ => return Err(malformed(&token_list.span)),
+        };
+
+        let return_type = match &tokens[position..] {
+            [Token::Verb(Verb::Colon, _), Token::Noun(value::Value::VarName(type_name), type_span), ..] => {
+                position += 2;
+                Some(parse_type(type_name, type_span)?)
+            },
+            _ => None,
+        };
+
+        match tokens.get(position) {
+            Some(Token::Verb(Verb::OpenParen, _)) => position += 1,
+            Some(token) => return Err(malformed(token.span())),
+            None => return Err(malformed(&token_list.span)),
+        }
+
+        let mut params: Vec<Parameter> = Vec::new();
+        loop {
+            match &tokens[position..] {
+                [Token::Verb(Verb::CloseParen, _)] if params.is_empty() => break,
+                [Token::Noun(value::Value::VarName(param), _), Token::Verb(Verb::Colon, _), Token::Noun(value::Value::VarName(type_name), type_span), ..] => {
+                    params.push(Parameter { name: param.clone(), param_type: Some(parse_type(type_name, type_span)?) });
+                    position += 3;
+                },
+                [Token::Noun(value::Value::VarName(param), _), ..] => {
+                    params.push(Parameter { name: param.clone(), param_type: None });
+                    position += 1;
+                },
+                [token, ..] => return Err(malformed(token.span())),
+                [] => return Err(malformed(&token_list.span)),
+            }
+
+            match &tokens[position..] {
+                [Token::Verb(Verb::Comma, _), ..] => position += 1,
+                [Token::Verb(Verb::CloseParen, _)] => break,
+                [token, ..] => return Err(malformed(token.span())),
+                [] => return Err(malformed(&token_list.span)),
+            }
+        }
+
+        Ok((name, params, return_type))
+    }
+
+    // Parse lines from `index` until the `}` closing the block opened at `open`, or the end of the file for the top level
+    // Returns the statements and the span of the closing line
+    fn parse_block(&self, index: &mut usize, open: Option<&Span>, diagnostics: &mut Vec<Diagnostic>) -> (Vec<AST_statement>, Option<Span>) {
+        let lines = &self.lexer.tokenized_lines.lines;
         let mut statements: Vec<AST_statement> = Vec::new();
 
-        for token_list in &self.lexer.tokenized_lines.lines {
-            let suffix = match &token_list.suffix {
-                Some(val) => val,
-                None => panic!("Failed to load line suffix"),
-            };
+        while let Some(token_list) = lines.get(*index) {
+            *index += 1;
 
-            match suffix {
-                tokenized::Suffix::Set => {
-                    let statement_type = AST_type::Set;
-                    let mut eq_side = false;
-                    // **GOAL** We need to identify the variable name, and from there evaluate expression
-                    // Load variable name and right hand expression and extract parenthesees 
-                    // Extraction format for right side: [Noun, Verb, [Noun, Verb]]
-                    let mut right_hand: Vec<Token> = Vec::new();
-                    let mut a = value::Value::VarName(String::new());
-                    for token in &token_list.objects {
-                        match token {
-                            tokenized::Token::Noun(val) => {
-                                match val {
-                                    value::Value::VarName(name) => {
-                                        if !eq_side {
-                                            a = val.clone();
-                                        }
-                                        else {
-                                            right_hand.push(Token::Noun(val.clone()));
-                                        }
-                                    }
-                                    _ => {
-                                        right_hand.push(Token::Noun(val.clone()));
-                                    }
-                                }
-                            },
-                            tokenized::Token::Verb(val) => {
-                                match val {
-                                    tokenized::Verb::Set => {
-                                        eq_side = true;
-                                    },
-                                    _ => {
-                                        right_hand.push(Token::Verb(val.clone()));
-                                    }
-                                }
-                            }
-                        }
+            match token_list.suffix {
+                // The lexer always gives a `}` a statement of its own
+                Some(tokenized::Suffix::Close) => {
+                    if open.is_none() {
+                        diagnostics.push(Diagnostic::error("E0208", "unexpected '}' with no open block".to_string(), Some(token_list.span.clone())));
+                        continue;
                     }
+                    return (statements, Some(token_list.span.clone()));
+                },
+                Some(tokenized::Suffix::Open) => match token_list.objects.first() {
+                    Some(Token::Keyword(Keyword::If, _)) => statements.extend(self.parse_if(token_list, index, diagnostics)),
+                    Some(Token::Keyword(Keyword::While, _)) => statements.extend(self.parse_while(token_list, index, diagnostics)),
+                    Some(Token::Keyword(Keyword::For, _)) => statements.extend(self.parse_for(token_list, index, diagnostics)),
+                    Some(Token::Keyword(Keyword::Else, span)) => {
+                        diagnostics.push(Diagnostic::error("E0213", "`else` without a matching `if`".to_string(), Some(span.clone()))
+                            .with_help("`else` must follow the '}' closing an `if` block".to_string()));
+                        self.parse_block(index, Some(&token_list.span), diagnostics);
+                    },
+                    _ => statements.extend(self.parse_function(token_list, index, diagnostics)),
+                },
+                _ => match Self::parse_statement(token_list) {
+                    Ok(statement) => statements.push(statement),
+                    Err(err) => diagnostics.push(err),
+                },
+            }
+        }
 
-                    // Take right side of the expression and build a AST statement from it
-                    
-                    statements.push(AST_statement {
-                        statement_type: statement_type,
-                        a: a,
-                        b: right_hand,
-                    });
+        if let Some(open) = open {
+            diagnostics.push(Diagnostic::error("E0209", "unclosed '{'".to_string(), Some(open.clone()))
+                .with_help("add a '}' to close the block".to_string()));
+        }
+
+        (statements, None)
+    }
+
+    fn parse_function(&self, token_list: &TokenList, index: &mut usize, diagnostics: &mut Vec<Diagnostic>) -> Option<AST_statement> {
+        // The body is parsed even when the header is malformed, so its lines aren't mistaken for top level statements
+        let header = Self::parse_function_header(token_list);
+        let (body, close) = self.parse_block(index, Some(&token_list.span), diagnostics);
+
+        match header {
+            Ok((name, params, return_type)) => {
+                let span = close.map_or(token_list.span.clone(), |close| token_list.span.to(&close));
+                let function = Function {
+                    name: name.clone(),
+                    params,
+                    return_type,
+                    body: Self::build_ast(body, diagnostics),
+                    span: token_list.span.clone(),
+                };
+
+                Some(AST_statement {
+                    statement_type: AST_type::Function,
+                    a: value::Value::VarName(name),
+                    declared_type: None,
+                    b: Expression::Noun(value::Value::Function(Rc::new(function)), token_list.span.clone()),
+                    span,
+                    doc: token_list.doc.clone(),
+                })
+            },
+            Err(err) => {
+                diagnostics.push(err);
+                None
+            },
+        }
+    }
+
+    // The tokens between a block keyword and the '{', e.g. the `x < 3` of `while x < 3 {`
+    fn parse_condition(tokens: &[Token], keyword: &Keyword, header: &TokenList) -> Result<Expression, Diagnostic> {
+        if tokens.is_empty() {
+            return Err(Diagnostic::error("E0213", format!("expected a condition after `{}`", keyword.name()), Some(header.span.clone())));
+        }
+
+        ExpressionParser::new(tokens, &header.span).parse()
+    }
+
+    // `if a {` followed by any number of `} else if b {` arms and an optional final `} else {`
+    fn parse_if(&self, header: &TokenList, index: &mut usize, diagnostics: &mut Vec<Diagnostic>) -> Vec<AST_statement> {
+        let lines = &self.lexer.tokenized_lines.lines;
+        let label = self.label("if");
+        let mut branches: Vec<Branch> = Vec::new();
+        let mut failed = false;
+        let mut header = header;
+
+        let end_span = loop {
+            let (condition, last) = match header.objects.as_slice() {
+                [Token::Keyword(Keyword::If, _), condition @ ..] | [Token::Keyword(Keyword::Else, _), Token::Keyword(Keyword::If, _), condition @ ..] => {
+                    (Some(Self::parse_condition(condition, &Keyword::If, header)), false)
                 },
-                tokenized::Suffix::Return => {
-                    statements.push(AST_statement {
-                        statement_type: AST_type::Return,
-                        a: value::Value::Undefined,
-                        b: token_list.objects.clone(),
-                    });
-                }
-                _ => {
-                    panic!("Unknown suffix");
-                }
+                [Token::Keyword(Keyword::Else, _)] => (None, true),
+                _ => (Some(Err(Diagnostic::error("E0213", "expected `{` or `if` after `else`".to_string(), Some(header.span.clone())))), true),
+            };
+
+            let condition = match condition {
+                Some(Ok(condition)) => Some(condition),
+                Some(Err(err)) => {
+                    diagnostics.push(err);
+                    failed = true;
+                    None
+                },
+                None => None,
             };
+
+            let (body, close) = self.parse_block(index, Some(&header.span), diagnostics);
+            let end_span = close.unwrap_or_else(|| header.span.clone());
+            branches.push(Branch { condition, body, span: header.span.clone() });
+
+            match lines.get(*index) {
+                Some(next) if !last && matches!(next.suffix, Some(tokenized::Suffix::Open)) && matches!(next.objects.first(), Some(Token::Keyword(Keyword::Else, _))) => {
+                    *index += 1;
+                    header = next;
+                },
+                _ => break end_span,
+            }
+        };
+
+        if failed {
+            return Vec::new();
+        }
+        desugar::lower_if(&label, branches, &end_span)
+    }
+
+    // `while a {`
+    fn parse_while(&self, header: &TokenList, index: &mut usize, diagnostics: &mut Vec<Diagnostic>) -> Vec<AST_statement> {
+        let label = self.label("while");
+        let condition = Self::parse_condition(&header.objects[1..], &Keyword::While, header);
+        let (body, close) = self.parse_block(index, Some(&header.span), diagnostics);
+
+        match condition {
+            Ok(condition) => desugar::lower_while(&label, condition, body, &header.span, &close.unwrap_or_else(|| header.span.clone())),
+            Err(err) => {
+                diagnostics.push(err);
+                Vec::new()
+            },
+        }
+    }
+
+    // `for name in list {`
+    fn parse_for(&self, header: &TokenList, index: &mut usize, diagnostics: &mut Vec<Diagnostic>) -> Vec<AST_statement> {
+        let label = self.label("for");
+        let parts = match header.objects.as_slice() {
+            [Token::Keyword(Keyword::For, _), Token::Noun(value::Value::VarName(name), _), Token::Keyword(Keyword::In, _), iterable @ ..] => {
+                Self::parse_condition(iterable, &Keyword::In, header).map(|iterable| (name.clone(), iterable))
+            },
+            _ => Err(Diagnostic::error("E0213", "malformed `for` loop".to_string(), Some(header.span.clone()))
+                .with_help("loops over a list have the form `for name in list {`".to_string())),
+        };
+        let (body, close) = self.parse_block(index, Some(&header.span), diagnostics);
+
+        match parts {
+            Ok((name, iterable)) => desugar::lower_for(&label, &name, iterable, body, &header.span, &close.unwrap_or_else(|| header.span.clone())),
+            Err(err) => {
+                diagnostics.push(err);
+                Vec::new()
+            },
+        }
+    }
+
+    // Jumps only reach markers in their own block, so every jump is resolved as soon as its block is complete
+    fn build_ast(statements: Vec<AST_statement>, diagnostics: &mut Vec<Diagnostic>) -> AST {
+        let ast = AST::new(statements);
+
+        for (index, statement) in ast.statements.iter().enumerate() {
+            if let (AST_type::Do(lookup), value::Value::VarName(name)) = (&statement.statement_type, &statement.a)
+                && ast.jump_target(name, lookup, index).is_none() {
+                let message = match lookup {
+                    Lookup::Forward => format!("no marker `{}` after this jump", name),
+                    Lookup::Back => format!("no marker `{}` before this jump", name),
+                    Lookup::Anywhere => format!("no marker `{}` in this block", name),
+                };
+
+                let mut diagnostic = Diagnostic::error("E0212", message, Some(statement.span.clone()));
+                if ast.markers.contains_key(name) {
+                    diagnostic = diagnostic.with_help(format!("write `~{}` to jump back or `*{}` to jump to the first `{}!` in the block", name, name, name));
+                }
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        ast
+    }
+
+    // A failed statement is recorded and parsing resumes at the next statement, which starts after a suffix or a line break that `continues()` doesn't carry over
+    pub fn run(&self) -> Result<AST, Vec<Diagnostic>> {
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let (statements, _) = self.parse_block(&mut 0, None, &mut diagnostics);
+        let mut ast = Self::build_ast(statements, &mut diagnostics);
+        ast.strict = self.lexer.strict;
+
+        if diagnostics.is_empty() {
+            Ok(ast)
+        } else {
+            Err(diagnostics)
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::emit::expression_text;
+
+    fn parse_source(source: &str) -> Result<AST, Vec<Diagnostic>> {
+        let mut lexer = Lexer::from_source("<test>".to_string(), source.to_string());
+        lexer.run()?;
+        Parser::new(lexer).run()
+    }
+
+    fn parse_return(source: &str) -> Expression {
+        parse_source(source).unwrap().statements.remove(0).b
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        let expression = parse_return("5 + 2 * 3");
+        assert!(matches!(expression, Expression::Binary(Verb::Add, _, ref right, _) if matches!(**right, Expression::Binary(Verb::Mult, _, _, _))));
+    }
+
+    #[test]
+    fn binary_verbs_are_left_associative() {
+        assert_eq!(expression_text(&parse_return("5 + 2 * 3")), "(5 + (2 * 3))");
+        assert_eq!(expression_text(&parse_return("10 - 4 - 3")), "((10 - 4) - 3)");
+        assert_eq!(expression_text(&parse_return("24 / 4 / 2 * 3")), "(((24 / 4) / 2) * 3)");
+        assert_eq!(expression_text(&parse_return("1 - 2 * 3 + 4")), "((1 - (2 * 3)) + 4)");
+    }
 
-        AST { statements }
+    #[test]
+    fn parentheses_override_precedence() {
+        let expression = parse_return("(5 + 2) * 3");
+        assert!(matches!(expression, Expression::Binary(Verb::Mult, ref left, _, _) if matches!(**left, Expression::Binary(Verb::Add, _, _, _))));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn comparisons_and_logic_follow_precedence() {
+        // `<=` is one verb, not `<` followed by `=`
+        let expression = parse_return("a + 1 <= b");
+        assert!(matches!(expression, Expression::Binary(Verb::LessEq, ref left, _, _) if matches!(**left, Expression::Binary(Verb::Add, _, _, _))));
+
+        let expression = parse_return("a or b and c");
+        assert!(matches!(expression, Expression::Binary(Verb::Or, _, ref right, _) if matches!(**right, Expression::Binary(Verb::And, _, _, _))));
+
+        let expression = parse_return("not a == b");
+        assert!(matches!(expression, Expression::Unary(Verb::Not, ref operand, _) if matches!(**operand, Expression::Binary(Verb::Eq, _, _, _))));
+    }
+
+    #[test]
+    fn casts_bind_tighter_than_binary_verbs() {
+        let expression = parse_return("x:int < 120:float");
+        assert!(matches!(expression, Expression::Binary(Verb::Less, ref left, ref right, _)
+            if matches!(**left, Expression::Cast(_, value::Type::Int, _)) && matches!(**right, Expression::Cast(_, value::Type::Float, _))));
+
+        let expression = parse_return("-xs[0]:str");
+        assert!(matches!(expression, Expression::Unary(Verb::Sub, ref operand, _) if matches!(**operand, Expression::Cast(ref inner, value::Type::Str, _) if matches!(**inner, Expression::Index(_, _, _)))));
+
+        assert_eq!(parse_source("x:number").unwrap_err()[0].code, "E0206");
+        assert_eq!(parse_source("x: + 1").unwrap_err()[0].code, "E0206");
+    }
+
+    #[test]
+    fn reads_pragmas_before_the_first_statement() {
+        assert!(parse_source("// settings\n#strict\nx = 1;").unwrap().strict);
+        assert!(!parse_source("x = 1;").unwrap().strict);
+
+        let codes: Vec<&str> = parse_source("#fast\nx = 1;\n#strict").unwrap_err().iter().map(|diagnostic| diagnostic.code).collect();
+        assert_eq!(codes, ["E0102", "E0103"]);
+    }
+
+    #[test]
+    fn prefix_minus_builds_unary_node() {
+        let expression = parse_return("-x");
+        assert!(matches!(expression, Expression::Unary(Verb::Sub, ref operand, _) if matches!(**operand, Expression::Noun(value::Value::VarName(_), _))));
+    }
+
+    #[test]
+    fn nested_parentheses_are_unwrapped() {
+        let expression = parse_return("((((7))))");
+        assert!(matches!(expression, Expression::Noun(value::Value::Int(7), _)));
+    }
+
+    #[test]
+    fn missing_values_name_the_verb_found_instead() {
+        let messages: Vec<String> = ["x = );", "1 + * 2", "y = 2 and;"].iter()
+            .map(|source| parse_source(source).unwrap_err().remove(0).message)
+            .collect();
+        assert_eq!(messages, ["expected a value, found ')'", "expected a value, found '*'", "expected a value, found the end of the statement"]);
+    }
+
+    #[test]
+    fn unclosed_parenthesis_is_rejected() {
+        let errors = parse_source("(1 + 2").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, "E0203");
+    }
+
+    #[test]
+    fn parses_typed_declarations() {
+        let statement = parse_source("x: float = 10.7823;").unwrap().statements.remove(0);
+        assert!(matches!(statement.a, value::Value::VarName(ref name) if name == "x"));
+        assert_eq!(statement.declared_type, Some(value::Type::Float));
+
+        let errors = parse_source("x: number = 1;\nx: = 1;").unwrap_err();
+        let codes: Vec<&str> = errors.iter().map(|error| error.code).collect();
+        assert_eq!(codes, ["E0206", "E0204"]);
+    }
+
+    #[test]
+    fn recovers_at_statement_boundaries() {
+        let errors = parse_source("x = 1 +;\ny = 2;\n= 3;\n(y * 2\ny").unwrap_err();
+        let codes: Vec<&str> = errors.iter().map(|error| error.code).collect();
+        assert_eq!(codes, ["E0201", "E0204", "E0203"]);
+
+        let lines: Vec<usize> = errors.iter().map(|error| error.span.as_ref().unwrap().line).collect();
+        assert_eq!(lines, [1, 3, 4]);
+    }
+
+    #[test]
+    fn parses_calls_with_arguments() {
+        let expression = parse_return("1 + f(a, 2 * b)");
+        assert!(matches!(expression, Expression::Binary(Verb::Add, _, ref right, _)
+            if matches!(**right, Expression::Call(ref name, ref args, _) if name == "f" && args.len() == 2)));
+        assert!(matches!(parse_return("g()"), Expression::Call(_, ref args, _) if args.is_empty()));
+        assert_eq!(parse_source("f(1, 2").unwrap_err()[0].code, "E0203");
+    }
+
+    #[test]
+    fn parses_function_declarations() {
+        let statement = parse_source("main: int (y: int, z) {\n    x = y;\n    x\n}").unwrap().statements.remove(0);
+        assert!(matches!(statement.statement_type, AST_type::Function));
+        assert_eq!((statement.span.line, statement.span.end_line), (1, 4));
+
+        match statement.b {
+            Expression::Noun(value::Value::Function(function), _) => {
+                assert_eq!(function.name, "main");
+                assert_eq!(function.return_type, Some(value::Type::Int));
+                assert_eq!(function.params[0].param_type, Some(value::Type::Int));
+                assert_eq!(function.params[1].param_type, None);
+                assert_eq!(function.body.statements.len(), 2);
+            },
+            _ => panic!("Expected a function"),
+        }
+    }
+
+    #[test]
+    fn reports_malformed_blocks() {
+        let errors = parse_source("f: int (x: int {\n    x\n}\n}\ng () {\n    1").unwrap_err();
+        let codes: Vec<&str> = errors.iter().map(|error| error.code).collect();
+        assert_eq!(codes, ["E0207", "E0208", "E0209"]);
+    }
+
+    #[test]
+    fn parses_markers_and_jumps() {
+        let ast = parse_source("top!\nend if x - 1?\n~top?\n*top if y?\nend!").unwrap();
+        let kinds: Vec<AST_type> = ast.statements.iter().map(|statement| statement.statement_type.clone()).collect();
+        assert!(matches!(kinds.as_slice(), [AST_type::Mark, AST_type::Do(Lookup::Forward), AST_type::Do(Lookup::Back), AST_type::Do(Lookup::Anywhere), AST_type::Mark]));
+        assert_eq!(ast.markers["top"], [0]);
+        assert_eq!(ast.jump_target("end", &Lookup::Forward, 1), Some(4));
+    }
+
+    #[test]
+    fn rejects_jumps_without_a_marker() {
+        let errors = parse_source("back!\nback?\nnowhere if 1?\n~later?\nlater!").unwrap_err();
+        let codes: Vec<&str> = errors.iter().map(|error| error.code).collect();
+        assert_eq!(codes, ["E0212", "E0212", "E0212"]);
+
+        let errors = parse_source("a b!\nm x?\nif!").unwrap_err();
+        let codes: Vec<&str> = errors.iter().map(|error| error.code).collect();
+        assert_eq!(codes, ["E0211", "E0211", "E0211"]);
+    }
+
+    #[test]
+    fn lowers_structured_blocks_to_markers_and_jumps() {
+        let ast = parse_source("while x {\n    x = x - 1;\n}").unwrap();
+        let kinds: Vec<AST_type> = ast.statements.iter().map(|statement| statement.statement_type.clone()).collect();
+        assert!(matches!(kinds.as_slice(), [
+            AST_type::Mark, AST_type::Do(Lookup::Forward), AST_type::Set, AST_type::Do(Lookup::Back), AST_type::Mark,
+        ]));
+        assert!(ast.markers.keys().all(|name| name.starts_with("#while0.")));
+
+        // `} else {` closes one arm and opens the next
+        let ast = parse_source("if a {\n    1\n} else {\n    2\n}").unwrap();
+        assert_eq!(ast.statements.iter().filter(|statement| matches!(statement.statement_type, AST_type::Return)).count(), 2);
+    }
+
+    #[test]
+    fn parses_lists_and_indexing() {
+        let expression = parse_return("[1, x][0] * 2");
+        assert!(matches!(expression, Expression::Binary(Verb::Mult, ref left, _, _)
+            if matches!(**left, Expression::Index(ref list, _, _) if matches!(**list, Expression::List(ref items, _) if items.len() == 2))));
+        assert_eq!(parse_source("[1, 2").unwrap_err()[0].code, "E0203");
+        assert_eq!(parse_source("x[1").unwrap_err()[0].code, "E0203");
+    }
+
+    #[test]
+    fn reports_malformed_structured_blocks() {
+        let errors = parse_source("if {\n}\nx = 1;\nelse {\n}\nfor x [1] {\n}\nwhile 1 {\n} else {\n}").unwrap_err();
+        let codes: Vec<&str> = errors.iter().map(|error| error.code).collect();
+        assert_eq!(codes, ["E0213", "E0213", "E0213", "E0213"]);
+    }
+
+    #[test]
+    fn interpolated_strings_parse_to_concatenations() {
+        let expression = parse_return("\"a {x + 1}b{y}\"");
+        match expression {
+            Expression::Concat(parts, _) => {
+                assert!(matches!(parts.as_slice(), [
+                    Expression::Noun(value::Value::Str(a), _),
+                    Expression::Binary(Verb::Add, _, _, _),
+                    Expression::Noun(value::Value::Str(b), _),
+                    Expression::Noun(value::Value::VarName(y), _),
+                ] if a == "a " && b == "b" && y == "y"));
+                assert_eq!((parts[1].span().column, parts[1].span().end_column), (5, 9));
+            },
+            _ => panic!("Expected a concatenation"),
+        }
+
+        let errors = parse_source("\"{}\"").unwrap_err();
+        assert_eq!(errors[0].code, "E0201");
+    }
+
+    #[test]
+    fn doc_comments_attach_to_the_next_declaration() {
+        let source = "/// Adds one.\n/// Takes an int.\ninc (n: int) {\n    /// Unused.\n    n + 1\n}\n\n/// The start.\nx = inc(1);\nx";
+        let statements = parse_source(source).unwrap().statements;
+
+        assert_eq!(statements[0].doc.as_deref(), Some("Adds one.\nTakes an int."));
+        assert_eq!(statements[1].doc.as_deref(), Some("The start."));
+        assert_eq!(statements[2].doc, None);
+
+        // A doc comment on something that isn't a declaration is dropped
+        match &statements[0].b {
+            Expression::Noun(value::Value::Function(function), _) => assert_eq!(function.body.statements[0].doc, None),
+            _ => panic!("Expected a function"),
+        }
+    }
+
+    #[test]
+    fn suggests_a_semicolon_for_unterminated_sets() {
+        let errors = parse_source("x = 1\ny = 2;").unwrap_err();
+        assert_eq!(errors[0].code, "E0202");
+        assert_eq!(errors[0].notes[0].message, "end a set statement with ';'");
+    }
+
+    #[test]
+    fn spans_point_at_source_columns() {
+        let expression = parse_return("  1 + (foo * 3)");
+        let span = expression.span();
+        assert_eq!((span.line, span.column, span.end_column), (1, 3, 14));
+
+        match expression {
+            Expression::Binary(_, _, right, _) => assert_eq!((right.span().column, right.span().end_column), (8, 14)),
+            _ => panic!("Expected a binary expression"),
+        }
+    }
+}
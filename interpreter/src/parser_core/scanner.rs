@@ -0,0 +1,661 @@
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::diagnostics::diagnostic::Diagnostic;
+use crate::parser_core::span::Span;
+use crate::parser_core::tokenized::{Keyword, Segment, Suffix, Token, Verb};
+use crate::parser_core::value::Value;
+
+// **GOAL:** Walk the source one character at a time, so string and char literals are read whole before anything else can split them
+
+// What the scanner produces, the lexer groups it into statements
+#[derive(Clone, Debug)]
+pub enum Lexeme {
+    Token(Token),
+    Suffix(Suffix, Span),      // `; { } ! ?`
+    Pragma(String, Span),      // `#strict`, the name without the `#`
+    Doc(String),               // `/// text`, one line of documentation for the next declaration
+    Newline,                   // End of a physical line
+}
+
+fn single_verb(character: char) -> Option<Verb> {
+    match character {
+        '+' => Some(Verb::Add),
+        '-' => Some(Verb::Sub),
+        '*' => Some(Verb::Mult),
+        '/' => Some(Verb::Div),
+        '=' => Some(Verb::Set),
+        '(' => Some(Verb::OpenParen),
+        ')' => Some(Verb::CloseParen),
+        ':' => Some(Verb::Colon),
+        ',' => Some(Verb::Comma),
+        '~' => Some(Verb::Tilde),
+        '[' => Some(Verb::OpenBracket),
+        ']' => Some(Verb::CloseBracket),
+        '<' => Some(Verb::Less),
+        '>' => Some(Verb::Greater),
+        _ => None,
+    }
+}
+
+// Checked before the single character verbs so `<=` isn't read as `<` followed by `=`
+fn double_verb(first: char, second: char) -> Option<Verb> {
+    match (first, second) {
+        ('=', '=') => Some(Verb::Eq),
+        ('!', '=') => Some(Verb::NotEq),
+        ('<', '=') => Some(Verb::LessEq),
+        ('>', '=') => Some(Verb::GreaterEq),
+        _ => None,
+    }
+}
+
+fn suffix(character: char) -> Option<Suffix> {
+    match character {
+        ';' => Some(Suffix::Set),
+        '{' => Some(Suffix::Open),
+        '}' => Some(Suffix::Close),
+        '!' => Some(Suffix::Mark),
+        '?' => Some(Suffix::Do),
+        _ => None,
+    }
+}
+
+// Characters that end a word, everything else (letters, digits, `.`, `_`) is read as part of it
+fn ends_word(character: char) -> bool {
+    character.is_whitespace() || character == '"' || character == '\'' || single_verb(character).is_some() || suffix(character).is_some()
+}
+
+// A whole numeric literal as written, `_` separators and the `L` / `n` suffixes included
+// Integers without a suffix take the smallest of int, long and bigint they fit in, a `.` or an exponent makes a float
+fn number(text: &str, span: &Span) -> Result<Value, Diagnostic> {
+    let invalid = |help: &str| Diagnostic::error("E0108", format!("invalid numeric literal `{}`", text), Some(span.clone()))
+        .with_help(help.to_string());
+
+    let (body, suffix) = match (text.strip_suffix('L'), text.strip_suffix('n')) {
+        (Some(body), _) => (body, Some('L')),
+        (_, Some(body)) => (body, Some('n')),
+        _ => (text, None),
+    };
+
+    let (radix, base, digits) = match body.get(..2) {
+        Some("0x" | "0X") => (16, "hexadecimal", &body[2..]),
+        Some("0o" | "0O") => (8, "octal", &body[2..]),
+        Some("0b" | "0B") => (2, "binary", &body[2..]),
+        _ => (10, "decimal", body),
+    };
+
+    let characters: Vec<char> = digits.chars().collect();
+    for (index, character) in characters.iter().enumerate() {
+        let between_digits = index > 0 && characters[index - 1].is_digit(radix) && characters.get(index + 1).is_some_and(|next| next.is_digit(radix));
+        if *character == '_' && !between_digits {
+            return Err(invalid("`_` can only separate two digits, as in `1_000_000`"));
+        }
+    }
+    let cleaned: String = characters.into_iter().filter(|character| *character != '_').collect();
+
+    if radix != 10 {
+        if cleaned.is_empty() {
+            return Err(invalid(&format!("add {} digits after the `{}`", base, &body[..2])));
+        }
+        if let Some(digit) = cleaned.chars().find(|character| !character.is_digit(radix)) {
+            return Err(Diagnostic::error("E0108", format!("invalid digit `{}` in {} literal `{}`", digit, base, text), Some(span.clone())));
+        }
+        return integer(BigInt::parse_bytes(cleaned.as_bytes(), radix).unwrap_or_default(), suffix, text, span);
+    }
+
+    // digits [. digits] [e [+-] digits], the whole part may be left out as in `.5`
+    let (mantissa, exponent) = match cleaned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent.strip_prefix(['+', '-']).unwrap_or(exponent))),
+        None => (cleaned.as_str(), None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    let is_digits = |part: &str| !part.is_empty() && part.chars().all(|character| character.is_ascii_digit());
+    let well_formed = (is_digits(whole) || whole.is_empty() && fraction.is_some())
+        && fraction.is_none_or(is_digits)
+        && exponent.is_none_or(is_digits);
+    if !well_formed {
+        return Err(invalid("numbers are written like `42`, `3.0`, `1_000`, `2.5e-3`, `0xff`, `0o17` or `0b1010`"));
+    }
+
+    if fraction.is_none() && exponent.is_none() {
+        return integer(cleaned.parse().unwrap_or_default(), suffix, text, span);
+    }
+
+    if let Some(suffix) = suffix {
+        return Err(Diagnostic::error("E0108", format!("a float literal can't take the `{}` suffix", suffix), Some(span.clone()))
+            .with_help("only integer literals take the `L` and `n` suffixes".to_string()));
+    }
+
+    match cleaned.parse::<f64>() {
+        Ok(val) if val.is_finite() => Ok(Value::Float(val)),
+        _ => Err(Diagnostic::error("E0109", format!("literal `{}` is out of range for `float`", text), Some(span.clone()))),
+    }
+}
+
+fn integer(val: BigInt, suffix: Option<char>, text: &str, span: &Span) -> Result<Value, Diagnostic> {
+    match suffix {
+        Some('n') => Ok(Value::BigInt(val)),
+        Some(_) => val.to_i64().map(Value::Long).ok_or_else(|| Diagnostic::error("E0109", format!("literal `{}` is out of range for `long`", text), Some(span.clone()))
+            .with_help("use the `n` suffix for a bigint".to_string())),
+        None => Ok(match (val.to_i32(), val.to_i64()) {
+            (Some(n), _) => Value::Int(n),
+            (None, Some(n)) => Value::Long(n),
+            (None, None) => Value::BigInt(val),
+        }),
+    }
+}
+
+pub struct Scanner {
+    file: Rc<str>,
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+    column: usize,
+    line_has_lexemes: bool,   // Whether anything but whitespace was read on the current line, a `#` only starts a pragma at the beginning of a line
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Scanner {
+    pub fn new(file: Rc<str>, source: &str) -> Self {
+        Scanner {
+            file,
+            chars: source.chars().collect(),
+            position: 0,
+            line: 1,
+            column: 1,
+            line_has_lexemes: false,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let character = self.peek(0)?;
+        self.position += 1;
+        if character == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(character)
+    }
+
+    // Span from a start position to the character before the current one
+    fn span_from(&self, line: usize, column: usize) -> Span {
+        Span::new(self.file.clone(), line, column, self.line, self.column.saturating_sub(1).max(column))
+    }
+
+    fn skip_line(&mut self) {
+        while self.peek(0).is_some_and(|character| character != '\n') {
+            self.advance();
+        }
+    }
+
+    pub fn run(mut self) -> (Vec<Lexeme>, Vec<Diagnostic>) {
+        let mut lexemes = Vec::new();
+        while let Some(lexeme) = self.next_lexeme() {
+            lexemes.push(lexeme);
+        }
+
+        (lexemes, self.diagnostics)
+    }
+
+    // Skips whitespace and comments, None at the end of the source
+    fn next_lexeme(&mut self) -> Option<Lexeme> {
+        while let Some(character) = self.peek(0) {
+            let (line, column) = (self.line, self.column);
+
+            if character == '\n' {
+                self.advance();
+                self.line_has_lexemes = false;
+                return Some(Lexeme::Newline);
+            }
+
+            if character.is_whitespace() {
+                self.advance();
+                continue;
+            }
+
+            // `///` documents the next declaration, `////` and longer runs are ordinary comments
+            if character == '/' && self.peek(1) == Some('/') && self.peek(2) == Some('/') && self.peek(3) != Some('/') {
+                let start = self.position + 3;
+                self.skip_line();
+                let text: String = self.chars[start..self.position].iter().collect();
+                let text = text.strip_prefix(' ').unwrap_or(&text).trim_end().to_string();
+                return Some(Lexeme::Doc(text));
+            }
+
+            // A `//` outside of a literal comments out the rest of the line
+            if character == '/' && self.peek(1) == Some('/') {
+                self.skip_line();
+                continue;
+            }
+
+            // A block comment that spans lines ends the statement it started on, like the line break it replaces
+            if character == '/' && self.peek(1) == Some('*') {
+                if self.block_comment() {
+                    self.line_has_lexemes = false;
+                    return Some(Lexeme::Newline);
+                }
+                continue;
+            }
+
+            if character == '#' && !self.line_has_lexemes {
+                self.advance();
+                let mut name = String::new();
+                while let Some(next) = self.peek(0).filter(|next| next.is_alphanumeric() || *next == '_') {
+                    name.push(next);
+                    self.advance();
+                }
+                self.line_has_lexemes = true;
+                return Some(Lexeme::Pragma(name, self.span_from(line, column)));
+            }
+
+            self.line_has_lexemes = true;
+
+            if character == '"' || character == '\'' {
+                return Some(Lexeme::Token(self.literal(character)));
+            }
+
+            if let Some(verb) = self.peek(1).and_then(|next| double_verb(character, next)) {
+                self.advance();
+                self.advance();
+                return Some(Lexeme::Token(Token::Verb(verb, self.span_from(line, column))));
+            }
+
+            if let Some(verb) = single_verb(character) {
+                self.advance();
+                return Some(Lexeme::Token(Token::Verb(verb, self.span_from(line, column))));
+            }
+
+            if let Some(suffix) = suffix(character) {
+                self.advance();
+                return Some(Lexeme::Suffix(suffix, self.span_from(line, column)));
+            }
+
+            if character.is_ascii_digit() || character == '.' && self.peek(1).is_some_and(|next| next.is_ascii_digit()) {
+                return Some(Lexeme::Token(self.number()));
+            }
+
+            let mut word = String::new();
+            while let Some(next) = self.peek(0).filter(|next| !ends_word(*next)) {
+                word.push(next);
+                self.advance();
+            }
+
+            return Some(Lexeme::Token(self.word(word, self.span_from(line, column))));
+        }
+
+        None
+    }
+
+    // Skips a `/* */` comment starting at the current character, comments nest so `/* a /* b */ c */` is one comment
+    // Returns whether the comment contained a line break
+    fn block_comment(&mut self) -> bool {
+        let (line, column) = (self.line, self.column);
+        self.advance();
+        self.advance();
+
+        let mut depth = 1;
+        while depth > 0 {
+            match (self.peek(0), self.peek(1)) {
+                (Some('/'), Some('*')) => {
+                    depth += 1;
+                    self.advance();
+                },
+                (Some('*'), Some('/')) => {
+                    depth -= 1;
+                    self.advance();
+                },
+                (None, _) => {
+                    self.diagnostics.push(Diagnostic::error("E0110", "unterminated block comment".to_string(), Some(Span::new(self.file.clone(), line, column, line, column + 1)))
+                        .with_help("add a `*/` to close it, comments nest so every `/*` needs its own `*/`".to_string()));
+                    break;
+                },
+                _ => {},
+            }
+            self.advance();
+        }
+
+        self.line > line
+    }
+
+    // A quoted literal starting at the current character, a literal must close on the line it opens
+    // A malformed literal is reported and still produces a token, so the parser doesn't report the same problem again
+    fn literal(&mut self, quote: char) -> Token {
+        let (line, column) = (self.line, self.column);
+        self.advance();
+
+        let mut text = String::new();
+        let mut text_start = (self.line, self.column);
+        let mut segments = Vec::new();
+        let mut valid = true;
+
+        loop {
+            match self.peek(0) {
+                None | Some('\n') => {
+                    let (kind, close) = if quote == '"' { ("string", "\"") } else { ("char", "'") };
+                    self.diagnostics.push(Diagnostic::error("E0104", format!("unterminated {} literal", kind), Some(self.span_from(line, column)))
+                        .with_help(format!("add a `{}` before the end of the line", close)));
+                    return Token::Noun(Value::Str(text), self.span_from(line, column));
+                },
+                Some('{') if quote == '"' => {
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text), self.span_from(text_start.0, text_start.1)));
+                    }
+                    match self.interpolation() {
+                        Some(segment) => segments.push(segment),
+                        None => return Token::Noun(Value::Str(text), self.span_from(line, column)),
+                    }
+                    text_start = (self.line, self.column);
+                },
+                Some(character) if character == quote => {
+                    if !text.is_empty() && !segments.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text), self.span_from(text_start.0, text_start.1)));
+                    }
+                    self.advance();
+                    break;
+                },
+                Some('\\') => {
+                    let (escape_line, escape_column) = (self.line, self.column);
+                    self.advance();
+                    match self.escape() {
+                        Some(character) => text.push(character),
+                        // Only the first bad escape of a literal is reported, reading goes on to the closing quote
+                        None if valid => {
+                            valid = false;
+                            self.diagnostics.push(Diagnostic::error("E0105", "invalid escape sequence".to_string(), Some(self.span_from(escape_line, escape_column)))
+                                .with_help("the available escapes are \\n, \\t, \\r, \\0, \\\", \\', \\\\, \\{, \\} and \\u{...}".to_string()));
+                        },
+                        None => {},
+                    }
+                },
+                Some(character) => {
+                    text.push(character);
+                    self.advance();
+                },
+            }
+        }
+
+        let span = self.span_from(line, column);
+        if quote == '"' && segments.is_empty() {
+            return Token::Noun(Value::Str(text), span);
+        }
+        if quote == '"' {
+            return Token::Interpolated(segments, span);
+        }
+
+        let mut characters = text.chars();
+        match (characters.next(), characters.next()) {
+            (Some(character), None) => Token::Noun(Value::Char(character), span),
+            _ => {
+                self.diagnostics.push(Diagnostic::error("E0106", "a char literal must hold exactly one character".to_string(), Some(span.clone()))
+                    .with_help("use double quotes to write a string".to_string()));
+                Token::Noun(Value::Str(text), span)
+            },
+        }
+    }
+
+    // The tokens of a `{expression}` inside a string, starting at the `{`
+    // Anything that ends a statement is reported, None when the braces aren't closed before the end of the line
+    fn interpolation(&mut self) -> Option<Segment> {
+        let (line, column) = (self.line, self.column);
+        self.advance();
+
+        let mut tokens = Vec::new();
+        loop {
+            while self.peek(0).is_some_and(|character| character.is_whitespace() && character != '\n') {
+                self.advance();
+            }
+
+            match (self.peek(0), self.peek(1)) {
+                (Some('}'), _) => {
+                    self.advance();
+                    return Some(Segment::Expression(tokens, self.span_from(line, column)));
+                },
+                // A comment would run past the closing quote
+                (None | Some('\n'), _) | (Some('/'), Some('/')) => {
+                    self.diagnostics.push(Diagnostic::error("E0104", "unterminated interpolation".to_string(), Some(self.span_from(line, column)))
+                        .with_help("add a `}` to close the interpolation, or write `\\{` for a literal brace".to_string()));
+                    return None;
+                },
+                _ => {},
+            }
+
+            match self.next_lexeme() {
+                Some(Lexeme::Token(token)) => tokens.push(token),
+                Some(Lexeme::Suffix(suffix, span)) => self.diagnostics.push(
+                    Diagnostic::error("E0107", format!("`{}` can only end a statement", suffix.symbol()), Some(span))
+                        .with_help("an interpolation holds a single expression".to_string()),
+                ),
+                _ => {},
+            }
+        }
+    }
+
+    // The character after a `\`, None when the escape isn't valid
+    fn escape(&mut self) -> Option<char> {
+        let character = match self.peek(0)? {
+            '\n' => return None,
+            character => character,
+        };
+        self.advance();
+
+        match character {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '"' => Some('"'),
+            '\'' => Some('\''),
+            '\\' => Some('\\'),
+            '{' => Some('{'),
+            '}' => Some('}'),
+            // `\u{1F600}`, one to six hex digits naming a unicode scalar value
+            'u' if self.peek(0) == Some('{') => {
+                self.advance();
+                let mut digits = String::new();
+                while let Some(digit) = self.peek(0).filter(|digit| digit.is_ascii_hexdigit()) {
+                    digits.push(digit);
+                    self.advance();
+                }
+
+                if self.peek(0) != Some('}') {
+                    return None;
+                }
+                self.advance();
+
+                if digits.is_empty() || digits.len() > 6 {
+                    return None;
+                }
+                u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32)
+            },
+            _ => None,
+        }
+    }
+
+    // A word starting with a digit, read up to the first character that ends a word
+    // The sign of an exponent is part of the literal, `1e-5` is one float rather than `1e` minus `5`
+    fn number(&mut self) -> Token {
+        let (line, column) = (self.line, self.column);
+        let mut text = String::new();
+
+        while let Some(next) = self.peek(0) {
+            let exponent_sign = matches!(next, '+' | '-') && text.ends_with(['e', 'E']) && !text.starts_with("0x") && !text.starts_with("0X");
+            if ends_word(next) && !exponent_sign {
+                break;
+            }
+            text.push(next);
+            self.advance();
+        }
+
+        let span = self.span_from(line, column);
+        match number(&text, &span) {
+            Ok(val) => Token::Noun(val, span),
+            Err(diagnostic) => {
+                self.diagnostics.push(diagnostic);
+                Token::Noun(Value::Undefined, span)
+            },
+        }
+    }
+
+    // Converts an unquoted word to a keyword, a word verb (`and`) or a Noun
+    fn word(&mut self, word: String, span: Span) -> Token {
+        if let Some(keyword) = Keyword::from_name(&word) {
+            return Token::Keyword(keyword, span);
+        }
+
+        if let Some(verb) = Verb::from_word(&word) {
+            return Token::Verb(verb, span);
+        }
+
+        let val = Value::evaluate(word.clone());
+        if let Value::Undefined = val {
+            self.diagnostics.push(Diagnostic::error("E0101", format!("unrecognized value `{}`", word), Some(span.clone()))
+                .with_help("wrap text in double quotes to write a string".to_string()));
+        }
+
+        Token::Noun(val, span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> (Vec<Lexeme>, Vec<Diagnostic>) {
+        Scanner::new(Rc::from("<test>"), source).run()
+    }
+
+    fn nouns(source: &str) -> Vec<Value> {
+        let (lexemes, diagnostics) = scan(source);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        lexemes.into_iter()
+            .filter_map(|lexeme| match lexeme {
+                Lexeme::Token(Token::Noun(val, _)) => Some(val),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_literals_before_operators_and_comments() {
+        assert!(matches!(nouns("x = \"a+b\";").as_slice(), [Value::VarName(_), Value::Str(s)] if s == "a+b"));
+        assert!(matches!(nouns("\"x = 1; // not a comment\" // a comment").as_slice(), [Value::Str(s)] if s == "x = 1; // not a comment"));
+        assert!(matches!(nouns("'?'").as_slice(), [Value::Char('?')]));
+    }
+
+    #[test]
+    fn decodes_escapes() {
+        assert!(matches!(nouns(r#""tab\tquote\" slash\\ line\n""#).as_slice(), [Value::Str(s)] if s == "tab\tquote\" slash\\ line\n"));
+        assert!(matches!(nouns(r#""\u{48}\u{1F600}""#).as_slice(), [Value::Str(s)] if s == "H\u{1F600}"));
+        assert!(matches!(nouns(r"'\''").as_slice(), [Value::Char('\'')]));
+    }
+
+    #[test]
+    fn splits_interpolated_strings_into_segments() {
+        let (lexemes, diagnostics) = scan("\"Hi {name}, {\"n{1}\"}!\" \"\\{plain\\}\"");
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+        match &lexemes[0] {
+            Lexeme::Token(Token::Interpolated(segments, _)) => assert!(matches!(segments.as_slice(), [
+                Segment::Text(hi, _),
+                Segment::Expression(name, _),
+                Segment::Text(comma, _),
+                Segment::Expression(nested, _),
+                Segment::Text(bang, _),
+            ] if hi == "Hi " && comma == ", " && bang == "!" && name.len() == 1 && matches!(nested.as_slice(), [Token::Interpolated(_, _)]))),
+            other => panic!("Expected an interpolated string, found {:?}", other),
+        }
+        assert!(matches!(&lexemes[1], Lexeme::Token(Token::Noun(Value::Str(s), _)) if s == "{plain}"));
+
+        let (_, diagnostics) = scan("\"{x\"\n\"{x; y}\"");
+        let codes: Vec<&str> = diagnostics.iter().map(|diagnostic| diagnostic.code).collect();
+        assert_eq!(codes, ["E0104", "E0104", "E0107"]);
+    }
+
+    #[test]
+    fn skips_nested_block_comments() {
+        assert!(matches!(nouns("1 /* a /* b */ c */ 2").as_slice(), [Value::Int(1), Value::Int(2)]));
+        assert!(matches!(nouns("\"/* kept */\"").as_slice(), [Value::Str(s)] if s == "/* kept */"));
+
+        // A comment over several lines still separates the statements around it
+        let (lexemes, _) = scan("x = 1; /* one\ntwo */ y = 2;");
+        assert_eq!(lexemes.iter().filter(|lexeme| matches!(lexeme, Lexeme::Newline)).count(), 1);
+
+        let (_, diagnostics) = scan("x = 1;\n/* a /* b */");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].code, diagnostics[0].span.as_ref().unwrap().line), ("E0110", 2));
+    }
+
+    #[test]
+    fn keeps_doc_comments() {
+        let (lexemes, _) = scan("/// Adds one.\n///\n//// not documentation\n// nor this");
+        let docs: Vec<&str> = lexemes.iter()
+            .filter_map(|lexeme| match lexeme {
+                Lexeme::Doc(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(docs, ["Adds one.", ""]);
+    }
+
+    #[test]
+    fn reads_numeric_literals() {
+        assert!(matches!(nouns("0xff 0o17 0b1010 1_000_000").as_slice(), [Value::Int(255), Value::Int(15), Value::Int(10), Value::Int(1_000_000)]));
+        assert!(matches!(nouns("3 3.0 .5 1e10 2.5E-3").as_slice(), [Value::Int(3), Value::Float(a), Value::Float(b), Value::Float(c), Value::Float(d)]
+            if *a == 3.0 && *b == 0.5 && *c == 1e10 && *d == 2.5e-3));
+        assert!(matches!(nouns("0xFFFF_FFFF 0x10L 0b11n").as_slice(), [Value::Long(4_294_967_295), Value::Long(16), Value::BigInt(n)] if n.to_string() == "3"));
+
+        // The sign after an exponent belongs to the literal, anywhere else it is a verb
+        let (lexemes, _) = scan("1e-5-2");
+        assert!(matches!(lexemes.as_slice(), [Lexeme::Token(Token::Noun(Value::Float(_), _)), Lexeme::Token(Token::Verb(Verb::Sub, _)), Lexeme::Token(Token::Noun(Value::Int(2), _))]));
+    }
+
+    #[test]
+    fn reports_malformed_numeric_literals() {
+        let sources = [
+            ("1__0", "E0108"),
+            ("1_", "E0108"),
+            ("0b102", "E0108"),
+            ("0x", "E0108"),
+            ("12abc", "E0108"),
+            ("1.", "E0108"),
+            ("1.5L", "E0108"),
+            ("9223372036854775808L", "E0109"),
+            ("1e400", "E0109"),
+        ];
+
+        for (source, code) in sources {
+            let (lexemes, diagnostics) = scan(source);
+            assert_eq!(diagnostics.iter().map(|diagnostic| diagnostic.code).collect::<Vec<&str>>(), [code], "{}", source);
+            assert!(matches!(lexemes.as_slice(), [Lexeme::Token(Token::Noun(Value::Undefined, _))]), "{}", source);
+        }
+    }
+
+    #[test]
+    fn reports_malformed_literals_with_positions() {
+        let (_, diagnostics) = scan("x = 1;\ny = \"open;\nz = 'ab';\nw = \"\\q\";\nv = \"\\u{110000}\";");
+        let found: Vec<(&str, usize, usize)> = diagnostics.iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.span.as_ref().unwrap().line, diagnostic.span.as_ref().unwrap().column))
+            .collect();
+        assert_eq!(found, [("E0104", 2, 5), ("E0106", 3, 5), ("E0105", 4, 6), ("E0105", 5, 6)]);
+    }
+
+    #[test]
+    fn scanning_continues_after_an_unterminated_literal() {
+        let (lexemes, diagnostics) = scan("a = \"open\nb = 2;");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(lexemes.iter().any(|lexeme| matches!(lexeme, Lexeme::Token(Token::Noun(Value::Int(2), _)))));
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt;
+use std::rc::Rc;
+
+// Location of a token or AST node in the source, lines and columns are one based and the end is inclusive
+#[derive(Clone, Debug)]
+pub struct Span {
+    pub file: Rc<str>,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    pub fn new(file: Rc<str>, line: usize, column: usize, end_line: usize, end_column: usize) -> Self {
+        Span {
+            file,
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+
+    // Smallest span covering both self and other (other is expected to end after self)
+    pub fn to(&self, other: &Span) -> Span {
+        Span {
+            file: self.file.clone(),
+            line: self.line,
+            column: self.column,
+            end_line: other.end_line,
+            end_column: other.end_column,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
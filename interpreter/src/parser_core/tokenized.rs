@@ -1,31 +1,173 @@
 use crate::parser_core::value;
+use crate::parser_core::span::Span;
 
 #[derive(Clone, Debug)]
 pub enum Verb {
-    None,
     Add,
     Sub,
     Mult,
     Div,
     Set,
+    OpenParen,
+    CloseParen,
+    Colon,
+    Comma,
+    Tilde,
+    OpenBracket,
+    CloseBracket,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+impl Verb {
+    // Logical verbs are spelled as words and read like keywords
+    pub fn from_word(word: &str) -> Option<Verb> {
+        match word {
+            "and" => Some(Verb::And),
+            "or" => Some(Verb::Or),
+            "xor" => Some(Verb::Xor),
+            "not" => Some(Verb::Not),
+            _ => None,
+        }
+    }
+
+    // Source spelling of the verb
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Verb::Add => "+",
+            Verb::Sub => "-",
+            Verb::Mult => "*",
+            Verb::Div => "/",
+            Verb::Set => "=",
+            Verb::OpenParen => "(",
+            Verb::CloseParen => ")",
+            Verb::Colon => ":",
+            Verb::Comma => ",",
+            Verb::Tilde => "~",
+            Verb::OpenBracket => "[",
+            Verb::CloseBracket => "]",
+            Verb::Eq => "==",
+            Verb::NotEq => "!=",
+            Verb::Less => "<",
+            Verb::LessEq => "<=",
+            Verb::Greater => ">",
+            Verb::GreaterEq => ">=",
+            Verb::And => "and",
+            Verb::Or => "or",
+            Verb::Xor => "xor",
+            Verb::Not => "not",
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum Suffix {
     Set,
     Return,
+    Open,    // `{` opens a block, the following lines belong to it until the matching Close
+    Close,   // `}` alone on a line
+    Mark,    // `name!` places a marker
+    Do,      // `name if cond?` jumps to a marker
+}
+
+impl Suffix {
+    // Source spelling, Return has none since it is the absence of a suffix
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Suffix::Set => ";",
+            Suffix::Return => "",
+            Suffix::Open => "{",
+            Suffix::Close => "}",
+            Suffix::Mark => "!",
+            Suffix::Do => "?",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Suffix::Set => "set",
+            Suffix::Return => "return",
+            Suffix::Open => "open",
+            Suffix::Close => "close",
+            Suffix::Mark => "mark",
+            Suffix::Do => "do",
+        }
+    }
+}
+
+// Reserved words, they can't be used as variable names
+#[derive(Clone, Debug, PartialEq)]
+pub enum Keyword {
+    If,
+    Else,
+    While,
+    For,
+    In,
+}
+
+impl Keyword {
+    pub fn from_name(name: &str) -> Option<Keyword> {
+        match name {
+            "if" => Some(Keyword::If),
+            "else" => Some(Keyword::Else),
+            "while" => Some(Keyword::While),
+            "for" => Some(Keyword::For),
+            "in" => Some(Keyword::In),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Keyword::If => "if",
+            Keyword::Else => "else",
+            Keyword::While => "while",
+            Keyword::For => "for",
+            Keyword::In => "in",
+        }
+    }
+}
+
+// One piece of an interpolated string, `"a {b}"` is [Text("a "), Expression([b])]
+#[derive(Clone, Debug)]
+pub enum Segment {
+    Text(String, Span),
+    Expression(Vec<Token>, Span),   // The tokens between the braces, the span covers the braces
 }
 
 #[derive(Clone, Debug)]
 pub enum Token {
-    Verb(Verb),
-    Noun(value::Value),
+    Verb(Verb, Span),
+    Noun(value::Value, Span),
+    Keyword(Keyword, Span),
+    Interpolated(Vec<Segment>, Span),   // A string literal with at least one `{expression}` in it
+}
+
+impl Token {
+    pub fn span(&self) -> &Span {
+        match self {
+            Token::Verb(_, span) => span,
+            Token::Noun(_, span) => span,
+            Token::Keyword(_, span) => span,
+            Token::Interpolated(_, span) => span,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct TokenList {
     pub objects: Vec<Token>,
     pub suffix: Option<Suffix>,
+    pub span: Span,    // Covers the whole statement, suffix included
+    pub doc: Option<String>,   // The `///` lines right before the statement, joined with newlines
 }
 
 #[derive(Debug)]
@@ -1,16 +1,74 @@
 //**NOTE** This functionality is seperated from the runtime value as it includes parsing intermediary types
 
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
+
+use crate::parser_core::ast;
+
 #[derive(Debug, Clone)]
 pub enum Value {
     Int(i32),
+    Long(i64),
+    BigInt(BigInt),
     Str(String),
     Char(char),
     Float(f64),
+    Bool(bool),
     VarName(String),
+    Function(Rc<ast::Function>),
+    List(Vec<Value>),
     Undefined,
 }
 
 
+// Values of the same type compare by value and numbers compare with each other numerically, every other pair is unequal and unordered
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Char(a), Value::Char(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::Undefined, Value::Undefined) => true,
+            // Allow comparing numbers of different types
+            _ => self.numeric_cmp(other) == Some(Ordering::Equal),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a.partial_cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Str(a), Value::Str(b)) => a.partial_cmp(b),
+            (Value::Char(a), Value::Char(b)) => a.partial_cmp(b),
+            (Value::Bool(a), Value::Bool(b)) => a.partial_cmp(b),
+            // Shorter lists come first, lists of the same length compare item by item
+            (Value::List(a), Value::List(b)) => {
+                if a.len() != b.len() {
+                    return a.len().partial_cmp(&b.len());
+                }
+                for (x, y) in a.iter().zip(b.iter()) {
+                    match x.partial_cmp(y) {
+                        Some(Ordering::Equal) => {},
+                        ordering => return ordering,
+                    }
+                }
+                Some(Ordering::Equal)
+            },
+            _ => self.numeric_cmp(other),
+        }
+    }
+}
+
+
 // **NOTE** For each type there must be 3 associated functions: 
 //    1. an implementation in the evaluate type from value method, (Implemented as a function shared across all Values)
 //    2. an implementation in the evaluate from string method, (Implemented as a function shared across all Values)
@@ -21,15 +79,58 @@ pub trait CastTo<T> {
     fn cast_to(&self) -> Option<Value>;
 }
 
-// Implementation for casting to i32
+// Implementation for casting to i32, floats are truncated and fail when they are NaN, infinite or out of range
 impl CastTo<i32> for Value {
     fn cast_to(&self) -> Option<Value> {
         match self {
             Value::Int(n) => Some(Value::Int(*n)),
+            Value::Long(n) => i32::try_from(*n).ok().map(Value::Int),
+            Value::BigInt(n) => n.to_i32().map(Value::Int),
             Value::Str(s) => s.parse().ok().map(Value::Int),
             Value::Char(c) => Some(Value::Int(*c as i32)),
-            Value::Float(f) => Some(Value::Int(*f as i32)),
+            Value::Float(f) => f.to_i32().map(Value::Int),
+            Value::Bool(b) => Some(Value::Int(*b as i32)),
             Value::VarName(_) => None,
+            Value::Function(_) => None,
+            Value::List(_) => None,
+            Value::Undefined => None,
+        }
+    }
+}
+
+// Implementation for casting to i64, floats are truncated and fail when they are NaN, infinite or out of range
+impl CastTo<i64> for Value {
+    fn cast_to(&self) -> Option<Value> {
+        match self {
+            Value::Int(n) => Some(Value::Long(*n as i64)),
+            Value::Long(n) => Some(Value::Long(*n)),
+            Value::BigInt(n) => n.to_i64().map(Value::Long),
+            Value::Str(s) => s.parse().ok().map(Value::Long),
+            Value::Char(c) => Some(Value::Long(*c as i64)),
+            Value::Float(f) => f.to_i64().map(Value::Long),
+            Value::Bool(b) => Some(Value::Long(*b as i64)),
+            Value::VarName(_) => None,
+            Value::Function(_) => None,
+            Value::List(_) => None,
+            Value::Undefined => None,
+        }
+    }
+}
+
+// Implementation for casting to BigInt, floats are truncated and fail when they aren't finite
+impl CastTo<BigInt> for Value {
+    fn cast_to(&self) -> Option<Value> {
+        match self {
+            Value::Int(n) => Some(Value::BigInt(BigInt::from(*n))),
+            Value::Long(n) => Some(Value::BigInt(BigInt::from(*n))),
+            Value::BigInt(n) => Some(Value::BigInt(n.clone())),
+            Value::Str(s) => s.parse().ok().map(Value::BigInt),
+            Value::Char(c) => Some(Value::BigInt(BigInt::from(*c as u32))),
+            Value::Float(f) => BigInt::from_f64(f.trunc()).map(Value::BigInt),
+            Value::Bool(b) => Some(Value::BigInt(BigInt::from(*b as i32))),
+            Value::VarName(_) => None,
+            Value::Function(_) => None,
+            Value::List(_) => None,
             Value::Undefined => None,
         }
     }
@@ -40,10 +141,15 @@ impl CastTo<f64> for Value {
     fn cast_to(&self) -> Option<Value> {
         match self {
             Value::Int(n) => Some(Value::Float(*n as f64)),
+            Value::Long(n) => Some(Value::Float(*n as f64)),
+            Value::BigInt(n) => n.to_f64().map(Value::Float),
             Value::Str(s) => s.parse().ok().map(Value::Float),
             Value::Char(c) => Some(Value::Float(*c as i32 as f64)),
             Value::Float(f) => Some(Value::Float(*f)),
+            Value::Bool(b) => Some(Value::Float(*b as i32 as f64)),
             Value::VarName(_) => None,
+            Value::Function(_) => None,
+            Value::List(_) => None,
             Value::Undefined => None,
         }
     }
@@ -54,30 +160,67 @@ impl CastTo<String> for Value {
     fn cast_to(&self) -> Option<Value> {
         match self {
             Value::Int(n) => Some(Value::Str(n.to_string())),
+            Value::Long(n) => Some(Value::Str(n.to_string())),
+            Value::BigInt(n) => Some(Value::Str(n.to_string())),
             Value::Str(s) => Some(Value::Str(s.clone())),
             Value::Char(c) => Some(Value::Str(c.to_string())),
-            Value::Float(f) => Some(Value::Str(f.to_string())),
+            Value::Float(f) => Some(Value::Str(format!("{:?}", f))),   // `3.0` rather than `3`, so a float never reads as an int
+            Value::Bool(b) => Some(Value::Str(b.to_string())),
             Value::VarName(v) => Some(Value::Str(v.clone())),
+            Value::Function(function) => Some(Value::Str(format!("<function {}>", function.name))),
+            Value::List(items) => {
+                let items: Vec<String> = items.iter()
+                    .map(|item| match CastTo::<String>::cast_to(item) {
+                        Some(Value::Str(s)) => s,
+                        _ => "undefined".to_string(),
+                    })
+                    .collect();
+                Some(Value::Str(format!("[{}]", items.join(", "))))
+            },
             Value::Undefined => None,
         }
     }
 }
 
-// Implementation for casting to char
+// Implementation for casting to char, floats are truncated and fail when they are NaN, infinite, negative or not a code point
 impl CastTo<char> for Value {
     fn cast_to(&self) -> Option<Value> {
         match self {
             Value::Int(n) => char::from_u32(*n as u32).map(Value::Char),
+            Value::Long(n) => u32::try_from(*n).ok().and_then(char::from_u32).map(Value::Char),
+            Value::BigInt(n) => n.to_u32().and_then(char::from_u32).map(Value::Char),
             Value::Str(s) => {
-                if s.len() == 1 {
+                if s.chars().count() == 1 {
                     s.chars().next().map(Value::Char)
                 } else {
                     None
                 }
             },
             Value::Char(c) => Some(Value::Char(*c)),
-            Value::Float(f) => char::from_u32(*f as u32).map(Value::Char),
+            Value::Float(f) => f.to_u32().and_then(char::from_u32).map(Value::Char),
+            Value::Bool(_) => None,
             Value::VarName(_) => None,
+            Value::Function(_) => None,
+            Value::List(_) => None,
+            Value::Undefined => None,
+        }
+    }
+}
+
+// Implementation for casting to bool
+impl CastTo<bool> for Value {
+    fn cast_to(&self) -> Option<Value> {
+        match self {
+            Value::Int(n) => Some(Value::Bool(*n != 0)),
+            Value::Long(n) => Some(Value::Bool(*n != 0)),
+            Value::BigInt(n) => Some(Value::Bool(!n.is_zero())),
+            Value::Str(s) => s.parse().ok().map(Value::Bool),
+            Value::Char(_) => None,
+            Value::Float(f) => Some(Value::Bool(*f != 0.0)),
+            Value::Bool(b) => Some(Value::Bool(*b)),
+            Value::VarName(_) => None,
+            Value::Function(_) => None,
+            Value::List(_) => None,
             Value::Undefined => None,
         }
     }
@@ -90,6 +233,58 @@ impl CastTo<()> for Value {
     }
 }
 
+// Types that can be written in declarations (`x: float = 10.7823;`)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Int,
+    Long,
+    BigInt,
+    Float,
+    Str,
+    Char,
+    Bool,
+}
+
+impl Type {
+    pub fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "int" => Some(Type::Int),
+            "long" => Some(Type::Long),
+            "bigint" => Some(Type::BigInt),
+            "float" => Some(Type::Float),
+            "str" => Some(Type::Str),
+            "char" => Some(Type::Char),
+            "bool" => Some(Type::Bool),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Type::Int => "int",
+            Type::Long => "long",
+            Type::BigInt => "bigint",
+            Type::Float => "float",
+            Type::Str => "str",
+            Type::Char => "char",
+            Type::Bool => "bool",
+        }
+    }
+
+    // A value of this type, used as the target of cast_to_type
+    pub fn template(&self) -> Value {
+        match self {
+            Type::Int => Value::Int(0),
+            Type::Long => Value::Long(0),
+            Type::BigInt => Value::BigInt(BigInt::zero()),
+            Type::Float => Value::Float(0.0),
+            Type::Str => Value::Str(String::new()),
+            Type::Char => Value::Char('\0'),
+            Type::Bool => Value::Bool(false),
+        }
+    }
+}
+
 impl Value {
     pub fn evaluate(val: String) -> Value {   // Converts string representation of type to Value
         // Check if the value is wrapped in quotes (string)
@@ -104,49 +299,72 @@ impl Value {
             } else {
                 Value::Str(char_str.to_string()) // If not a single character, treat as string
             }
+        } else if val == "true" || val == "false" {
+            Value::Bool(val == "true")
+        } else if val.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            // Numbers never get here, the scanner reads every word starting with a digit as a numeric literal
+            Value::VarName(val)
         } else {
-            // First try to parse as float
-            if let Ok(float_val) = val.parse::<f64>() {
-                // If it's a whole number, return as Int
-                if float_val == float_val.trunc() {
-                    Value::Int(float_val as i32)
-                } else {
-                    Value::Float(float_val)
-                }
-            } else {
-                // If not a float, try as integer
-                match val.parse::<i32>() {
-                    Ok(int_val) => Value::Int(int_val),
-                    Err(_) => {
-                        // If it's not a number and not wrapped in quotes, treat as variable name
-                        if val.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                            Value::VarName(val)
-                        } else {
-                            Value::Undefined
-                        }
-                    }
-                }
-            }
+            Value::Undefined
         }
     }
 
     pub fn get_type(val: Value) -> Value {
         match val {
             Value::Int(_) => Value::Str("int".to_string()),
+            Value::Long(_) => Value::Str("long".to_string()),
+            Value::BigInt(_) => Value::Str("bigint".to_string()),
             Value::Str(_) => Value::Str("str".to_string()),
             Value::Char(_) => Value::Str("char".to_string()),
             Value::Float(_) => Value::Str("float".to_string()),
+            Value::Bool(_) => Value::Str("bool".to_string()),
             Value::VarName(_) => Value::Str("var".to_string()),
+            Value::Function(_) => Value::Str("function".to_string()),
+            Value::List(_) => Value::Str("list".to_string()),
             Value::Undefined => Value::Str("undefined".to_string()),
         }
     }
 
+    // Name of the value's type as a Rust string, for messages
+    pub fn type_name(&self) -> String {
+        match Value::get_type(self.clone()) {
+            Value::Str(name) => name,
+            _ => "undefined".to_string(),
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Value::Int(_) | Value::Long(_) | Value::BigInt(_) | Value::Float(_))
+    }
+
+    // Orders two numbers of any numeric types by value, None when either side isn't a number
+    // Integers are compared exactly, a float on either side compares both as floats
+    fn numeric_cmp(&self, other: &Value) -> Option<Ordering> {
+        if !self.is_number() || !other.is_number() {
+            return None;
+        }
+
+        match (self, other) {
+            (Value::Float(_), _) | (_, Value::Float(_)) => match (CastTo::<f64>::cast_to(self), CastTo::<f64>::cast_to(other)) {
+                (Some(Value::Float(a)), Some(Value::Float(b))) => a.partial_cmp(&b),
+                _ => None,
+            },
+            _ => match (CastTo::<BigInt>::cast_to(self), CastTo::<BigInt>::cast_to(other)) {
+                (Some(Value::BigInt(a)), Some(Value::BigInt(b))) => a.partial_cmp(&b),
+                _ => None,
+            },
+        }
+    }
+
     pub fn cast_to_type(&self, target: &Value) -> Value {
         match target {
             Value::Int(_) => CastTo::<i32>::cast_to(self).unwrap_or(Value::Undefined),
+            Value::Long(_) => CastTo::<i64>::cast_to(self).unwrap_or(Value::Undefined),
+            Value::BigInt(_) => CastTo::<BigInt>::cast_to(self).unwrap_or(Value::Undefined),
             Value::Float(_) => CastTo::<f64>::cast_to(self).unwrap_or(Value::Undefined),
             Value::Str(_) => CastTo::<String>::cast_to(self).unwrap_or(Value::Undefined),
             Value::Char(_) => CastTo::<char>::cast_to(self).unwrap_or(Value::Undefined),
+            Value::Bool(_) => CastTo::<bool>::cast_to(self).unwrap_or(Value::Undefined),
             _ => Value::Undefined,
         }
     }